@@ -0,0 +1,96 @@
+use std::fmt;
+
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::RequestBuilder;
+
+use crate::error::OrdClientError;
+
+const REDACTED: &str = "<redacted>";
+
+/// Credentials attached to every request made by `OrdClient`.
+#[derive(Clone)]
+pub enum Auth {
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    Bearer(String),
+    Header {
+        name: HeaderName,
+        value: HeaderValue,
+    },
+}
+
+impl Auth {
+    pub fn basic(username: impl Into<String>, password: impl Into<String>) -> Self {
+        Auth::Basic {
+            username: username.into(),
+            password: Some(password.into()),
+        }
+    }
+
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Auth::Bearer(token.into())
+    }
+
+    /// Arbitrary header such as `x-api-key`. The value is marked sensitive so it is
+    /// never printed by reqwest either.
+    pub fn header(name: &str, value: &str) -> Result<Self, OrdClientError> {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| OrdClientError::InvalidInput(format!("invalid auth header name {}", name)))?;
+        let mut value = HeaderValue::from_str(value).map_err(|_| {
+            OrdClientError::InvalidInput(format!("invalid value for auth header {}", name))
+        })?;
+        value.set_sensitive(true);
+        Ok(Auth::Header { name, value })
+    }
+
+    pub(crate) fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            Auth::Basic { username, password } => request.basic_auth(username, password.as_ref()),
+            Auth::Bearer(token) => request.bearer_auth(token),
+            Auth::Header { name, value } => request.header(name, value),
+        }
+    }
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Auth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &REDACTED)
+                .finish(),
+            Auth::Bearer(_) => f.debug_tuple("Bearer").field(&REDACTED).finish(),
+            Auth::Header { name, .. } => f
+                .debug_struct("Header")
+                .field("name", name)
+                .field("value", &REDACTED)
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let basic = format!("{:?}", Auth::basic("ord", "hunter2"));
+        assert!(basic.contains("ord"));
+        assert!(!basic.contains("hunter2"));
+        let bearer = format!("{:?}", Auth::bearer("s3cr3t-token"));
+        assert!(!bearer.contains("s3cr3t-token"));
+        let header = format!("{:?}", Auth::header("x-api-key", "k3y").unwrap());
+        assert!(header.contains("x-api-key"));
+        assert!(!header.contains("k3y"));
+    }
+
+    #[test]
+    fn rejects_invalid_header() {
+        assert!(Auth::header("bad header", "value").is_err());
+        assert!(Auth::header("x-api-key", "line\nbreak").is_err());
+    }
+}
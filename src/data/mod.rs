@@ -1,2 +1,3 @@
 pub(crate) mod transaction;
-pub(crate) mod rune_entry;
+pub mod rune_entry;
+pub mod supply;
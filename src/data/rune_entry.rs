@@ -1,66 +1,323 @@
-use ordinals::Terms;
+use bitcoin::Txid;
+use ordinals::{RuneId, SpacedRune, Terms};
+use serde::{Deserialize, Serialize};
 
-#[derive(serde::Deserialize)]
+use crate::data::supply::Supply;
+use crate::models::inscription::InscriptionId;
+
+/// Rune entry as served by ord's `/rune/{query}` and `/runes/{page}` endpoints.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RuneEntry {
-    pub spaced_rune: String,
+    pub block: u64,
+    pub burned: u128,
+    pub divisibility: u8,
+    pub etching: Txid,
     pub mints: u128,
+    pub number: u64,
     pub premine: u128,
-    pub divisibility: u16,
-    pub number: u128,
+    pub spaced_rune: SpacedRune,
+    pub symbol: Option<char>,
     pub terms: Option<Terms>,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub turbo: bool,
+}
+
+/// Why minting a rune is not possible at a given height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintClosedReason {
+    /// The rune was etched without mint terms.
+    Unmintable,
+    /// The mint window opens at `start`.
+    NotStarted { start: u64 },
+    /// The mint window closed at `end` (exclusive).
+    Ended { end: u64 },
+    /// All `cap` mints have been made.
+    Capped { cap: u128 },
+}
+
+/// Mint status of a rune at a given height, see [`RuneEntry::mint_status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MintStatus {
+    /// First height a mint is valid at, if the terms restrict it.
+    pub start: Option<u64>,
+    /// First height a mint is no longer valid at, if the terms restrict it.
+    pub end: Option<u64>,
+    pub remaining_mints: u128,
+    /// Base units each mint produces.
+    pub amount_per_mint: u128,
+    pub closed: Option<MintClosedReason>,
+}
+
+impl MintStatus {
+    pub fn is_open(&self) -> bool {
+        self.closed.is_none()
+    }
 }
 
 impl RuneEntry {
-    pub fn remaining_mints(&self) -> u128 {
-        if self.terms.is_none() {
-            return 0;
-        }
-        if let Some(cap) = self.terms.unwrap().cap {
-            return cap - self.mints;
+    /// First height mints are valid at, combining the absolute and offset terms like ord.
+    pub fn mint_start(&self) -> Option<u64> {
+        let terms = self.terms?;
+        let relative = terms.offset.0.map(|offset| self.block.saturating_add(offset));
+        let absolute = terms.height.0;
+        relative
+            .zip(absolute)
+            .map(|(relative, absolute)| relative.max(absolute))
+            .or(relative)
+            .or(absolute)
+    }
+
+    /// First height mints are no longer valid at.
+    pub fn mint_end(&self) -> Option<u64> {
+        let terms = self.terms?;
+        let relative = terms.offset.1.map(|offset| self.block.saturating_add(offset));
+        let absolute = terms.height.1;
+        relative
+            .zip(absolute)
+            .map(|(relative, absolute)| relative.min(absolute))
+            .or(relative)
+            .or(absolute)
+    }
+
+    /// Whether a mint confirmed in the block at `height` would be valid. To ask about the
+    /// next mint, pass `fetch_latest_block_height() + 1`.
+    pub fn mint_status(&self, height: u64) -> MintStatus {
+        let start = self.mint_start();
+        let end = self.mint_end();
+        let cap = self.terms.and_then(|terms| terms.cap).unwrap_or_default();
+        let remaining_mints = cap.saturating_sub(self.mints);
+        let closed = if self.terms.is_none() {
+            Some(MintClosedReason::Unmintable)
+        } else if let Some(start) = start.filter(|start| height < *start) {
+            Some(MintClosedReason::NotStarted { start })
+        } else if let Some(end) = end.filter(|end| height >= *end) {
+            Some(MintClosedReason::Ended { end })
+        } else if remaining_mints == 0 {
+            Some(MintClosedReason::Capped { cap })
+        } else {
+            None
+        };
+        MintStatus {
+            start,
+            end,
+            // no more mints can happen once the window has closed, whatever the cap says
+            remaining_mints: match closed {
+                Some(MintClosedReason::Ended { .. }) => 0,
+                _ => remaining_mints,
+            },
+            amount_per_mint: self.terms.and_then(|terms| terms.amount).unwrap_or_default(),
+            closed,
         }
-        return 0;
     }
+
+    pub fn remaining_mints(&self) -> u128 {
+        let cap = self.terms.and_then(|terms| terms.cap).unwrap_or_default();
+        cap.saturating_sub(self.mints)
+    }
+
+    pub fn supply(&self) -> Supply {
+        Supply::new(self)
+    }
+
+    /// Premine as a percentage of premine plus minted supply, rounded to 2 decimal places.
+    /// Zero without a premine or without terms. See [`Supply`] for the exact figures.
     pub fn premine_percentage(&self) -> f32 {
-        if self.premine == 0 {
+        if self.premine == 0 || self.terms.is_none() {
             return 0.0;
         }
-        if self.terms.is_none() {
-            return 0.0;
-        }
-        // normalize premine by dividing it to 10 ^ divisibility
-        let denominator = 10_i32.pow(self.divisibility.into());
-
-        let premine_normalized = if denominator != 0 {
-            self.premine
-                .div_ceil(10_i32.pow(self.divisibility.into()) as u128)
-        } else {
-            self.premine
-        };
-        let terms = self.terms.unwrap();
-        let total_mints_supply = terms.amount.unwrap() * self.mints;
-        let total_mints_normalized =
-            total_mints_supply.div_ceil(10_i32.pow(self.divisibility.into()) as u128);
-        let circulating_supply = total_mints_normalized + premine_normalized;
-        let premine_percentage = (premine_normalized * 100) as f32 / circulating_supply as f32;
+        let supply = self.supply();
+        let created = supply.premine.saturating_add(supply.minted);
+        let premine_percentage = supply.premine as f64 * 100.0 / created as f64;
         // round to 2 decimal places
-        return (premine_percentage * 100.0).round() / 100.0;
+        ((premine_percentage * 100.0).round() / 100.0) as f32
     }
 }
-#[derive(serde::Deserialize)]
+/// Response of ord's `/rune/{query}` endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct RuneResponse {
     pub entry: RuneEntry,
-    pub parent: Option<String>,
+    pub id: RuneId,
+    #[serde(default)]
+    pub mintable: bool,
+    pub parent: Option<InscriptionId>,
+}
+
+/// One page of ord's `/runes/{page}` endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RunesPage {
+    pub entries: Vec<(RuneId, RuneEntry)>,
+    pub more: bool,
+    pub prev: Option<u32>,
+    pub next: Option<u32>,
+}
+
+/// Entry of a plain rune without terms, for tests to override with struct update syntax.
+#[cfg(test)]
+pub(crate) fn base_entry() -> RuneEntry {
+    RuneEntry {
+        block: 840000,
+        burned: 0,
+        divisibility: 0,
+        etching: "e79134080a83fe3e0e06ed6990c5a9b63b362313341745707a2bff7d788a1375".parse().unwrap(),
+        mints: 0,
+        number: 0,
+        premine: 0,
+        spaced_rune: "RUNE".parse().unwrap(),
+        symbol: None,
+        terms: None,
+        timestamp: 1713571767,
+        turbo: false,
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
 
+    #[test]
+    fn test_rune_response() {
+        let json_data = r#"{
+            "entry": {
+                "block": 840000,
+                "burned": 2093590,
+                "divisibility": 5,
+                "etching": "e79134080a83fe3e0e06ed6990c5a9b63b362313341745707a2bff7d788a1375",
+                "mints": 0,
+                "number": 3,
+                "premine": 10000000000000000,
+                "spaced_rune": "DOG•GO•TO•THE•MOON",
+                "symbol": "🐕",
+                "terms": null,
+                "timestamp": 1713571767,
+                "turbo": true
+            },
+            "id": "840000:3",
+            "mintable": false,
+            "parent": "e79134080a83fe3e0e06ed6990c5a9b63b362313341745707a2bff7d788a1375i0"
+        }"#;
+        let response: RuneResponse = serde_json::from_str(json_data).unwrap();
+        assert_eq!(response.id, RuneId { block: 840000, tx: 3 });
+        assert!(!response.mintable);
+        assert_eq!(response.parent.unwrap().txid, response.entry.etching);
+        let entry = &response.entry;
+        assert_eq!(entry.block, 840000);
+        assert_eq!(entry.burned, 2093590);
+        assert_eq!(entry.divisibility, 5);
+        assert_eq!(entry.spaced_rune.to_string(), "DOG•GO•TO•THE•MOON");
+        assert_eq!(entry.symbol, Some('🐕'));
+        assert_eq!(entry.timestamp, 1713571767);
+        assert!(entry.turbo);
+        let round_trip: RuneResponse = serde_json::from_str(&serde_json::to_string(&response).unwrap()).unwrap();
+        assert_eq!(round_trip, response);
+    }
+
+    #[test]
+    fn test_runes_page() {
+        let json_data = r#"{
+            "entries": [
+                [
+                    "840000:3",
+                    {
+                        "block": 840000,
+                        "burned": 0,
+                        "divisibility": 5,
+                        "etching": "e79134080a83fe3e0e06ed6990c5a9b63b362313341745707a2bff7d788a1375",
+                        "mints": 0,
+                        "number": 3,
+                        "premine": 10000000000000000,
+                        "spaced_rune": "DOG•GO•TO•THE•MOON",
+                        "symbol": "🐕",
+                        "terms": null,
+                        "timestamp": 1713571767,
+                        "turbo": true
+                    }
+                ]
+            ],
+            "more": true,
+            "prev": null,
+            "next": 1
+        }"#;
+        let page: RunesPage = serde_json::from_str(json_data).unwrap();
+        assert_eq!(page.entries.len(), 1);
+        assert_eq!(page.entries[0].0, RuneId { block: 840000, tx: 3 });
+        assert_eq!(page.entries[0].1.spaced_rune.to_string(), "DOG•GO•TO•THE•MOON");
+        assert!(page.more);
+        assert_eq!(page.next, Some(1));
+    }
+
+    fn mintable_entry() -> RuneEntry {
+        RuneEntry {
+            block: 840000,
+            mints: 10,
+            terms: Some(Terms {
+                amount: Some(1000),
+                cap: Some(100),
+                height: (Some(840100), Some(850000)),
+                offset: (Some(50), Some(5000)),
+            }),
+            ..base_entry()
+        }
+    }
+
+    #[test]
+    fn test_mint_window_combines_height_and_offset() {
+        let entry = mintable_entry();
+        // start is the later of 840100 and 840000 + 50, end the earlier of 850000 and 845000
+        assert_eq!(entry.mint_start(), Some(840100));
+        assert_eq!(entry.mint_end(), Some(845000));
+        let only_offset = RuneEntry {
+            terms: Some(Terms {
+                offset: (Some(10), None),
+                ..entry.terms.unwrap()
+            }),
+            ..entry.clone()
+        };
+        assert_eq!(only_offset.mint_start(), Some(840100));
+        let no_window = RuneEntry {
+            terms: Some(Terms {
+                amount: Some(1),
+                cap: Some(1),
+                ..Default::default()
+            }),
+            ..entry
+        };
+        assert_eq!(no_window.mint_start(), None);
+        assert_eq!(no_window.mint_end(), None);
+    }
+
+    #[test]
+    fn test_mint_status() {
+        let entry = mintable_entry();
+        let open = entry.mint_status(840100);
+        assert!(open.is_open());
+        assert_eq!(open.remaining_mints, 90);
+        assert_eq!(open.amount_per_mint, 1000);
+        assert_eq!(open.start, Some(840100));
+        assert_eq!(open.end, Some(845000));
+
+        let not_started = entry.mint_status(840099);
+        assert_eq!(not_started.closed, Some(MintClosedReason::NotStarted { start: 840100 }));
+        assert_eq!(not_started.remaining_mints, 90);
+
+        let ended = entry.mint_status(845000);
+        assert_eq!(ended.closed, Some(MintClosedReason::Ended { end: 845000 }));
+        assert_eq!(ended.remaining_mints, 0);
+
+        let capped = RuneEntry { mints: 100, ..entry.clone() }.mint_status(840200);
+        assert_eq!(capped.closed, Some(MintClosedReason::Capped { cap: 100 }));
+
+        let unmintable = RuneEntry { terms: None, ..entry }.mint_status(840200);
+        assert_eq!(unmintable.closed, Some(MintClosedReason::Unmintable));
+        assert_eq!(unmintable.remaining_mints, 0);
+    }
+
     #[test]
     fn test_premine_percentage() {
         let rune_entry = RuneEntry {
             divisibility: 2,
-            spaced_rune: "Rune".to_string(),
             mints: 0,
             premine: 100,
             number: 1,
@@ -69,15 +326,32 @@ mod tests {
                 cap: Some(1000),
                 ..Default::default()
             }),
+            ..base_entry()
         };
         assert_eq!(rune_entry.premine_percentage(), 100.0);
+        assert_eq!(RuneEntry { premine: 0, ..rune_entry }.premine_percentage(), 0.0);
+        assert_eq!(RuneEntry { terms: None, ..rune_entry }.premine_percentage(), 0.0);
+    }
+
+    #[test]
+    fn test_premine_percentage_rounds() {
+        let rune_entry = RuneEntry {
+            mints: 1,
+            premine: 2,
+            terms: Some(Terms {
+                amount: Some(1),
+                cap: Some(10),
+                ..Default::default()
+            }),
+            ..base_entry()
+        };
+        assert_eq!(rune_entry.premine_percentage(), 66.67);
     }
 
     #[test]
     fn test_premine_percentage_nakamato() {
         let rune_entry = RuneEntry {
             divisibility: 0,
-            spaced_rune: "Rune".to_string(),
             mints: 168000,
             premine: 420000000000000,
             number: 6,
@@ -86,14 +360,33 @@ mod tests {
                 cap: Some(168000),
                 ..Default::default()
             }),
+            ..base_entry()
         };
         assert_eq!(rune_entry.premine_percentage(), 20.0);
     }
 
+    #[test]
+    fn test_premine_percentage_high_divisibility() {
+        let rune_entry = RuneEntry {
+            divisibility: 38,
+            mints: 3,
+            premine: 1,
+            terms: Some(Terms {
+                amount: Some(1),
+                cap: Some(10),
+                ..Default::default()
+            }),
+            ..base_entry()
+        };
+        assert_eq!(rune_entry.premine_percentage(), 25.0);
+        let over_minted = RuneEntry { mints: 11, ..rune_entry };
+        assert_eq!(over_minted.remaining_mints(), 0);
+    }
+
     #[test]
     fn test_premine_for_fehu() {
         let rune_entry = RuneEntry {
-            spaced_rune: "Fehu".to_string(),
+            spaced_rune: SpacedRune::from_str("FEHU").unwrap(),
             mints: 452105,
             divisibility: 2,
             premine: 11000000000,
@@ -103,6 +396,7 @@ mod tests {
                 cap: Some(1111111),
                 ..Default::default()
             }),
+            ..base_entry()
         };
         assert_eq!(rune_entry.premine_percentage(), 99.59);
     }
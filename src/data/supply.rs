@@ -0,0 +1,214 @@
+use crate::data::rune_entry::RuneEntry;
+use crate::models::amount::RuneAmount;
+
+/// Decimals kept in the percentages returned by [`Supply`], e.g. `99.59`.
+pub const PERCENT_DECIMALS: u8 = 2;
+
+/// Supply figures of a rune in base units, computed without floating point or overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Supply {
+    pub divisibility: u8,
+    pub premine: u128,
+    /// Premine plus every mint the terms allow.
+    pub max_supply: u128,
+    /// Units created by mints so far.
+    pub minted: u128,
+    pub burned: u128,
+    /// Premine plus minted, less burned.
+    pub circulating: u128,
+    pub mints: u128,
+    pub cap: u128,
+}
+
+impl Supply {
+    pub fn new(entry: &RuneEntry) -> Self {
+        let amount = entry.terms.and_then(|terms| terms.amount).unwrap_or_default();
+        let cap = entry.terms.and_then(|terms| terms.cap).unwrap_or_default();
+        // ord rejects etchings whose supply overflows, saturating only guards bad input
+        let minted = amount.saturating_mul(entry.mints);
+        let max_supply = entry.premine.saturating_add(amount.saturating_mul(cap));
+        let created = entry.premine.saturating_add(minted);
+        Supply {
+            divisibility: entry.divisibility,
+            premine: entry.premine,
+            max_supply,
+            minted,
+            burned: entry.burned,
+            circulating: created.saturating_sub(entry.burned),
+            mints: entry.mints,
+            cap,
+        }
+    }
+
+    pub fn max_supply_amount(&self) -> RuneAmount {
+        RuneAmount::new(self.max_supply, self.divisibility)
+    }
+
+    pub fn circulating_amount(&self) -> RuneAmount {
+        RuneAmount::new(self.circulating, self.divisibility)
+    }
+
+    pub fn minted_amount(&self) -> RuneAmount {
+        RuneAmount::new(self.minted, self.divisibility)
+    }
+
+    pub fn burned_amount(&self) -> RuneAmount {
+        RuneAmount::new(self.burned, self.divisibility)
+    }
+
+    /// Premine as a percentage of everything created so far (premine plus mints).
+    pub fn premine_share(&self) -> RuneAmount {
+        percentage(self.premine, self.premine.saturating_add(self.minted))
+    }
+
+    /// Premine as a percentage of the maximum supply.
+    pub fn premine_share_of_max(&self) -> RuneAmount {
+        percentage(self.premine, self.max_supply)
+    }
+
+    /// Mints made as a percentage of the cap.
+    pub fn mint_progress(&self) -> RuneAmount {
+        percentage(self.mints.min(self.cap), self.cap)
+    }
+}
+
+/// `numerator / denominator` as a percentage with [`PERCENT_DECIMALS`] decimals, rounded
+/// down. Zero when the denominator is zero.
+pub fn percentage(numerator: u128, denominator: u128) -> RuneAmount {
+    if denominator == 0 {
+        return RuneAmount::new(0, PERCENT_DECIMALS);
+    }
+    RuneAmount::new(
+        scaled_ratio(numerator, denominator, PERCENT_DECIMALS + 2),
+        PERCENT_DECIMALS,
+    )
+}
+
+/// `floor(numerator * 10^digits / denominator)` for any u128 operands, saturating if the
+/// result itself does not fit.
+pub fn scaled_ratio(numerator: u128, denominator: u128, digits: u8) -> u128 {
+    assert!(denominator != 0, "denominator must not be zero");
+    let mut quotient = numerator / denominator;
+    let mut remainder = numerator % denominator;
+    for _ in 0..digits {
+        let (digit, next) = mul_div_rem(remainder, 10, denominator);
+        remainder = next;
+        quotient = quotient
+            .saturating_mul(10)
+            .saturating_add(digit);
+    }
+    quotient
+}
+
+// (floor(remainder * factor / denominator), remainder * factor % denominator) for
+// remainder < denominator, without ever forming remainder * factor
+fn mul_div_rem(remainder: u128, factor: u32, denominator: u128) -> (u128, u128) {
+    let mut quotient = 0;
+    let mut accumulated = 0u128;
+    for _ in 0..factor {
+        if accumulated >= denominator - remainder {
+            quotient += 1;
+            accumulated -= denominator - remainder;
+        } else {
+            accumulated += remainder;
+        }
+    }
+    (quotient, accumulated)
+}
+
+#[cfg(test)]
+mod tests {
+    use ordinals::Terms;
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::data::rune_entry::base_entry;
+
+    fn entry(divisibility: u8, premine: u128, mints: u128, amount: Option<u128>, cap: Option<u128>) -> RuneEntry {
+        RuneEntry {
+            divisibility,
+            mints,
+            premine,
+            terms: Some(Terms {
+                amount,
+                cap,
+                ..Default::default()
+            }),
+            ..base_entry()
+        }
+    }
+
+    #[test]
+    fn supply_figures() {
+        let supply = Supply::new(&RuneEntry {
+            burned: 500,
+            ..entry(2, 10_000, 3, Some(1000), Some(10))
+        });
+        assert_eq!(supply.max_supply, 20_000);
+        assert_eq!(supply.minted, 3000);
+        assert_eq!(supply.circulating, 12_500);
+        assert_eq!(supply.max_supply_amount().to_string(), "200");
+        assert_eq!(supply.circulating_amount().to_string(), "125");
+        assert_eq!(supply.burned_amount().to_string(), "5");
+        assert_eq!(supply.premine_share().to_string(), "76.92");
+        assert_eq!(supply.premine_share_of_max().to_string(), "50");
+        assert_eq!(supply.mint_progress().to_string(), "30");
+    }
+
+    #[test]
+    fn no_amount_or_terms_does_not_panic() {
+        let supply = Supply::new(&entry(38, u128::MAX, 5, None, Some(10)));
+        assert_eq!(supply.max_supply, u128::MAX);
+        assert_eq!(supply.minted, 0);
+        assert_eq!(supply.premine_share().to_string(), "100");
+        let premine_only = Supply::new(&RuneEntry {
+            terms: None,
+            ..entry(0, 100, 0, None, None)
+        });
+        assert_eq!(premine_only.mint_progress().to_string(), "0");
+        assert_eq!(premine_only.premine_share().to_string(), "100");
+    }
+
+    #[test]
+    fn scaled_ratio_handles_large_operands() {
+        assert_eq!(scaled_ratio(1, 3, 4), 3333);
+        assert_eq!(scaled_ratio(u128::MAX, u128::MAX, 4), 10_000);
+        assert_eq!(scaled_ratio(u128::MAX - 1, u128::MAX, 4), 9999);
+        assert_eq!(scaled_ratio(u128::MAX / 2, u128::MAX, 2), 49);
+    }
+
+    proptest! {
+        #[test]
+        fn scaled_ratio_matches_direct_division(numerator in 0u128..u64::MAX as u128, denominator in 1u128..u64::MAX as u128) {
+            prop_assert_eq!(scaled_ratio(numerator, denominator, 4), numerator * 10_000 / denominator);
+        }
+
+        #[test]
+        fn shares_never_exceed_one_hundred_percent(
+            divisibility in 0u8..=38,
+            premine in any::<u128>(),
+            amount in any::<u128>(),
+            cap in any::<u128>(),
+            mints in any::<u128>(),
+        ) {
+            let supply = Supply::new(&entry(divisibility, premine, mints, Some(amount), Some(cap)));
+            let hundred = RuneAmount::new(10_000, PERCENT_DECIMALS);
+            prop_assert!(supply.premine_share().amount <= hundred.amount);
+            prop_assert!(supply.premine_share_of_max().amount <= hundred.amount);
+            prop_assert!(supply.mint_progress().amount <= hundred.amount);
+            prop_assert!(supply.circulating <= supply.premine.saturating_add(supply.minted));
+        }
+
+        #[test]
+        fn valid_etchings_keep_minted_within_max_supply(
+            premine in 0u128..(1 << 64),
+            amount in 0u128..(1 << 32),
+            cap in 0u128..(1 << 32),
+            mints in 0u128..(1 << 32),
+        ) {
+            let supply = Supply::new(&entry(8, premine, mints.min(cap), Some(amount), Some(cap)));
+            prop_assert!(supply.circulating <= supply.max_supply);
+            prop_assert_eq!(supply.max_supply, premine + amount * cap);
+        }
+    }
+}
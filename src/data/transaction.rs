@@ -1,3 +1,20 @@
+#[cfg(test)]
+use bitcoin::{absolute::LockTime, transaction::Version, Amount, ScriptBuf, Transaction, TxOut};
+
+/// Unsigned tx without inputs paying 546 sats to each of `scripts`.
+#[cfg(test)]
+pub(crate) fn tx_with_outputs(scripts: Vec<ScriptBuf>) -> Transaction {
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: scripts
+            .into_iter()
+            .map(|script_pubkey| TxOut { value: Amount::from_sat(546), script_pubkey })
+            .collect(),
+    }
+}
+
 #[cfg(test)]
 pub(crate) const RUNE_REVEAL_TX_HEX: &str = "02000000000101fc05137b6abfadf3d001d1ec5a6782443d5eed195799bbba78a73cc506e3f31b0000000000fdffffff031027000000000000225120baa218f69e4e85a84d0e11bb7fcc2458b49c32e88c6b5c8ae61218ae3d7c41b146451a0000000000225120fc25c2d84795c79e5015a6a35cfb6f6514d1bf4261f0332febf7b1f8df720d050000000000000000266a5d23020704ca8ddbb5a78aebb10b05a20106c09a0c0ae80708a0060cc1a2330e9cab341601034078d77f7227968908e80b19e5a2880e8617625ce49ab05db9528714b3427aa370a7a356a4322e23ead42988a2fc1e7b006aa610be2975424f8c5ca929a4fc446efd4503203fb562de74756594e01dc689a0b83e1931dbd1163f5541bf701befbb338d1819ac0063036f7264010118746578742f706c61696e3b636861727365743d7574662d38010200010d08cac6b67652ac630b004d0802202020202020202020205f5f5f5f5f5f5f5f5f5f2d2d2d2d2d2d2d5f5f5f5f20202020202020202020202020202020205f5f5f5f2d2d2d2d2d2d2d5f5f5f5f5f5f5f5f5f5f0a202020202020202020205c2d2d2d2d2d2d5f5f5f5f2d2d2d2d2d2d2d5f5f5f2d2d5f5f2d2d2d2d2d2d2d2d2d5f5f2d2d5f5f5f2d2d2d2d2d2d2d5f5f5f5f2d2d2d2d2d2d2f0a20202020202020202020205c2f2f2f2f2f2f2f2f202f202f202f202f202f205c2020205f2d2d2d2d2d2d2d5f2020202f205c205c205c205c205c205c5c5c5c5c5c5c5c2f0a202020202020202020202020205c2f2f2f2f2d2f2d2f2d2d2d2d2d2d2f5f2f5f7c202f5f5f5f2020205f5f5f5c207c5f5c5f5c2d2d2d2d2d2d5c2d5c2d5c5c5c5c2f0a2020202020202020202020202020202d2d2f2f2f2f202f202f20202f20202f2f7c7c20284f295c202f284f29207c7c5c5c20205c20205c205c205c5c5c5c2d2d0a20202020202020202020202020202020202020202d2d2d5f5f2f20202f2f202f7c205c5f20202f565c20205f2f207c5c205c5c20205c5f5f2d2d2d0a202020202020202020202020202020202020202020202020202d2f2f20202f202f5c5f202d2d2d2d2d2d2d205f2f5c205c20205c5c2d0a2020202020202020202020202020202020202020202020202020205c5f2f5f2f202f5c2d2d2d2d2d2d2d2d2d2f5c205c5f5c5f2f0a20202020202020202020204ce520202020202020202020202020202020202020202d2d2d2d5c2020207c2020202f2d2d2d2d0a2020202020202020202020202020202020202020202020202020202020202020202020207c202d7c2d207c0a20202020202020202020202020202020202020202020202020202020202020202020202f2020207c2020205c0a20202020202020202020202020202020202020202020202020202020202020202020202d2d2d2d205c5f5f5f7c0a202020202020202020202020202020202020202020206a6f696e207468652063756c74202d20646973636f72642e67672f686f6f7465720a6821c13fb562de74756594e01dc689a0b83e1931dbd1163f5541bf701befbb338d181900000000";
 #[cfg(test)]
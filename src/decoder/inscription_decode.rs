@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+use std::iter::Peekable;
+
+use bitcoin::blockdata::transaction::Transaction;
+use bitcoin::opcodes::all::{OP_ENDIF, OP_IF, OP_PUSHNUM_1, OP_PUSHNUM_16, OP_PUSHNUM_NEG1};
+use bitcoin::script::{Instruction, Instructions, Script};
+
+use crate::models::inscription::{InscriptionEnvelope, InscriptionPayload};
+
+const PROTOCOL_ID: &[u8] = b"ord";
+
+// field tags of an envelope, odd tags are optional and may be ignored
+const TAG_CONTENT_TYPE: u8 = 1;
+const TAG_POINTER: u8 = 2;
+const TAG_PARENT: u8 = 3;
+const TAG_METADATA: u8 = 5;
+const TAG_METAPROTOCOL: u8 = 7;
+const TAG_CONTENT_ENCODING: u8 = 9;
+const TAG_DELEGATE: u8 = 11;
+const TAG_RUNE: u8 = 13;
+
+// pushes of an envelope before they are sorted into fields
+struct RawEnvelope {
+    payload: Vec<Vec<u8>>,
+    pushnum: bool,
+}
+
+/// Decodes the `ord` inscription envelopes of a transaction's witnesses like ord does.
+#[derive(Debug, Clone)]
+pub struct InscriptionDecoder {}
+
+impl Default for InscriptionDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InscriptionDecoder {
+    pub fn new() -> Self {
+        InscriptionDecoder {}
+    }
+
+    /// Every envelope of every input's tapscript, in input order. An input whose tapscript
+    /// fails to parse yields no envelopes at all.
+    pub fn decode_tx(&self, transaction: &Transaction) -> Vec<InscriptionEnvelope> {
+        let mut envelopes = Vec::new();
+        for (input, tx_in) in transaction.input.iter().enumerate() {
+            // ord reads envelopes from script path spends of any leaf version
+            if let Some(leaf) = tx_in.witness.taproot_leaf_script() {
+                if let Some(input_envelopes) = Self::from_tapscript(leaf.script, input as u32) {
+                    envelopes.extend(input_envelopes);
+                }
+            }
+        }
+        envelopes
+    }
+
+    fn from_tapscript(tapscript: &Script, input: u32) -> Option<Vec<InscriptionEnvelope>> {
+        let mut envelopes = Vec::new();
+        let mut instructions = tapscript.instructions().peekable();
+        let mut stuttered = false;
+        while let Some(instruction) = instructions.next() {
+            if is_empty_push(&instruction.ok()?) {
+                let offset = envelopes.len() as u32;
+                let (stutter, raw) = Self::from_instructions(&mut instructions)?;
+                match raw {
+                    Some(raw) => envelopes.push(InscriptionEnvelope {
+                        input,
+                        offset,
+                        payload: parse_payload(raw.payload),
+                        pushnum: raw.pushnum,
+                        stutter: stuttered,
+                    }),
+                    None => stuttered = stutter,
+                }
+            }
+        }
+        Some(envelopes)
+    }
+
+    // after an OP_FALSE: `OP_IF "ord" <pushes> OP_ENDIF` is an envelope. Returns whether a
+    // non-envelope is directly followed by another OP_FALSE (a stutter) and the raw pushes,
+    // `None` when the script itself is invalid.
+    fn from_instructions(instructions: &mut Peekable<Instructions>) -> Option<(bool, Option<RawEnvelope>)> {
+        if !accept(instructions, |instruction| instruction.opcode() == Some(OP_IF))?
+            || !accept(instructions, |instruction| {
+                instruction.push_bytes().map(|bytes| bytes.as_bytes()) == Some(PROTOCOL_ID)
+            })?
+        {
+            let stutter = matches!(instructions.peek(), Some(Ok(instruction)) if is_empty_push(instruction));
+            return Some((stutter, None));
+        }
+        let mut pushnum = false;
+        let mut payload = Vec::new();
+        loop {
+            match instructions.next() {
+                None => return Some((false, None)),
+                Some(instruction) => match instruction.ok()? {
+                    Instruction::Op(OP_ENDIF) => return Some((false, Some(RawEnvelope { payload, pushnum }))),
+                    Instruction::Op(OP_PUSHNUM_NEG1) => {
+                        pushnum = true;
+                        payload.push(vec![0x81]);
+                    }
+                    Instruction::Op(op)
+                        if (OP_PUSHNUM_1.to_u8()..=OP_PUSHNUM_16.to_u8()).contains(&op.to_u8()) =>
+                    {
+                        pushnum = true;
+                        payload.push(vec![op.to_u8() - OP_PUSHNUM_1.to_u8() + 1]);
+                    }
+                    Instruction::PushBytes(push) => payload.push(push.as_bytes().to_vec()),
+                    Instruction::Op(_) => return Some((false, None)),
+                },
+            }
+        }
+    }
+}
+
+fn is_empty_push(instruction: &Instruction) -> bool {
+    matches!(instruction, Instruction::PushBytes(push) if push.is_empty())
+}
+
+// consumes the next instruction if it matches, `None` if it fails to parse
+fn accept(instructions: &mut Peekable<Instructions>, matches: impl Fn(&Instruction) -> bool) -> Option<bool> {
+    match instructions.peek() {
+        Some(Ok(instruction)) if matches(instruction) => {
+            instructions.next();
+            Some(true)
+        }
+        Some(Err(_)) => None,
+        _ => Some(false),
+    }
+}
+
+// pushes alternate tag and value until an empty tag, after which everything is body
+fn parse_payload(payload: Vec<Vec<u8>>) -> InscriptionPayload {
+    let body = payload
+        .iter()
+        .enumerate()
+        .position(|(i, push)| i % 2 == 0 && push.is_empty());
+    let mut fields: BTreeMap<&[u8], Vec<&[u8]>> = BTreeMap::new();
+    let mut incomplete_field = false;
+    for item in payload[..body.unwrap_or(payload.len())].chunks(2) {
+        match item {
+            [tag, value] => fields.entry(tag.as_slice()).or_default().push(value.as_slice()),
+            _ => incomplete_field = true,
+        }
+    }
+    let duplicate_field = fields.values().any(|values| values.len() > 1);
+
+    let content_encoding = take(&mut fields, TAG_CONTENT_ENCODING);
+    let content_type = take(&mut fields, TAG_CONTENT_TYPE);
+    let delegate = take(&mut fields, TAG_DELEGATE);
+    // metadata is chunked across as many pushes as needed
+    let metadata = fields
+        .remove([TAG_METADATA].as_slice())
+        .map(|chunks| chunks.concat());
+    let metaprotocol = take(&mut fields, TAG_METAPROTOCOL);
+    let parents = fields
+        .remove([TAG_PARENT].as_slice())
+        .unwrap_or_default()
+        .into_iter()
+        .map(<[u8]>::to_vec)
+        .collect();
+    let pointer = take(&mut fields, TAG_POINTER);
+    let rune = take(&mut fields, TAG_RUNE);
+    // an even tag left over, including a repeated known one, is a field ord does not understand
+    let unrecognized_even_field = fields
+        .keys()
+        .any(|tag| tag.first().is_some_and(|lsb| lsb % 2 == 0));
+
+    InscriptionPayload {
+        body: body.map(|i| payload[i + 1..].concat()),
+        content_encoding,
+        content_type,
+        delegate,
+        duplicate_field,
+        incomplete_field,
+        metadata,
+        metaprotocol,
+        parents,
+        pointer,
+        rune,
+        unrecognized_even_field,
+    }
+}
+
+// first value of a field, later duplicates stay behind
+fn take(fields: &mut BTreeMap<&[u8], Vec<&[u8]>>, tag: u8) -> Option<Vec<u8>> {
+    let key = [tag];
+    let values = fields.get_mut(key.as_slice())?;
+    if values.is_empty() {
+        return None;
+    }
+    let value = values.remove(0).to_vec();
+    if values.is_empty() {
+        fields.remove(key.as_slice());
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::consensus::deserialize;
+    use bitcoin::opcodes::all::OP_CHECKSIG;
+    use bitcoin::opcodes::OP_FALSE;
+    use bitcoin::script::{Builder, PushBytesBuf};
+    use bitcoin::{TxIn, Witness};
+    use hex::decode as hex_decode;
+    use ordinals::Rune;
+
+    use crate::data::transaction::{tx_with_outputs, NON_RUNE_TX, RUNE_REVEAL_TX_HEX};
+
+    use super::*;
+
+    fn push(builder: Builder, bytes: &[u8]) -> Builder {
+        builder.push_slice(PushBytesBuf::try_from(bytes.to_vec()).unwrap())
+    }
+
+    fn envelope(builder: Builder, pushes: &[&[u8]]) -> Builder {
+        let mut builder = push(builder.push_opcode(OP_FALSE).push_opcode(OP_IF), PROTOCOL_ID);
+        for bytes in pushes {
+            builder = push(builder, bytes);
+        }
+        builder.push_opcode(OP_ENDIF)
+    }
+
+    fn tx(tapscripts: Vec<Builder>) -> Transaction {
+        let mut tx = tx_with_outputs(vec![]);
+        tx.input = tapscripts
+            .into_iter()
+            .map(|tapscript| TxIn {
+                witness: Witness::from_slice(&[tapscript.into_bytes(), vec![0xc0; 33]]),
+                ..Default::default()
+            })
+            .collect();
+        tx
+    }
+
+    fn decode(tapscripts: Vec<Builder>) -> Vec<InscriptionEnvelope> {
+        InscriptionDecoder::new().decode_tx(&tx(tapscripts))
+    }
+
+    #[test]
+    fn decode_reveal_tx_inscription() {
+        let tx_bytes = hex_decode(RUNE_REVEAL_TX_HEX).expect("Invalid hex string");
+        let tx: Transaction = deserialize(&tx_bytes).expect("Failed to deserialize transaction");
+        let envelopes = InscriptionDecoder::new().decode_tx(&tx);
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(envelopes[0].input, 0);
+        assert_eq!(envelopes[0].offset, 0);
+        let payload = &envelopes[0].payload;
+        assert!(payload.content_type().unwrap().starts_with("text/plain"));
+        assert!(payload.body.as_ref().is_some_and(|body| !body.is_empty()));
+        assert!(payload.rune().is_some());
+        assert!(!payload.duplicate_field && !payload.incomplete_field && !payload.unrecognized_even_field);
+
+        let tx: Transaction = deserialize(&hex_decode(NON_RUNE_TX).unwrap()).unwrap();
+        assert!(InscriptionDecoder::new().decode_tx(&tx).is_empty());
+    }
+
+    #[test]
+    fn decode_every_field() {
+        let parent = [[7; 32].as_slice(), &[1]].concat();
+        let delegate = [9; 32];
+        let rune = Rune(99246114928149462);
+        let rune_bytes = rune.n().to_le_bytes();
+        let rune_bytes = &rune_bytes[..16 - rune.n().leading_zeros() as usize / 8];
+        let tapscript = envelope(
+            Builder::new(),
+            &[
+                &[TAG_CONTENT_TYPE],
+                b"text/plain;charset=utf-8",
+                &[TAG_CONTENT_ENCODING],
+                b"br",
+                &[TAG_METADATA],
+                &[0xa1, 0x61],
+                &[TAG_METADATA],
+                &[0x61, 0x01],
+                &[TAG_METAPROTOCOL],
+                b"brc-20",
+                &[TAG_PARENT],
+                &parent,
+                &[TAG_PARENT],
+                &[1, 2, 3],
+                &[TAG_DELEGATE],
+                &delegate,
+                &[TAG_POINTER],
+                &[0x10, 0x27],
+                &[TAG_RUNE],
+                rune_bytes,
+                &[],
+                b"hello ",
+                b"world",
+            ],
+        );
+        let envelopes = decode(vec![tapscript.push_slice([2; 32]).push_opcode(OP_CHECKSIG)]);
+        assert_eq!(envelopes.len(), 1);
+        let payload = &envelopes[0].payload;
+        assert_eq!(payload.body.as_deref(), Some(b"hello world".as_slice()));
+        assert_eq!(payload.content_type(), Some("text/plain;charset=utf-8"));
+        assert_eq!(payload.content_encoding(), Some("br"));
+        assert_eq!(payload.metadata.as_deref(), Some([0xa1, 0x61, 0x61, 0x01].as_slice()));
+        assert_eq!(payload.metaprotocol(), Some("brc-20"));
+        // the malformed parent is ignored by the typed accessor only
+        assert_eq!(payload.parents.len(), 2);
+        let parents = payload.parents();
+        assert_eq!(parents.len(), 1);
+        assert_eq!(parents[0].index, 1);
+        assert_eq!(payload.delegate().unwrap().index, 0);
+        assert_eq!(payload.pointer(), Some(10_000));
+        assert_eq!(payload.rune(), Some(rune));
+        // only the tag 5 duplicate, which is chunked metadata
+        assert!(payload.duplicate_field);
+        assert!(!payload.unrecognized_even_field);
+        assert!(!payload.incomplete_field);
+    }
+
+    #[test]
+    fn decode_flags() {
+        let duplicate_pointer = envelope(Builder::new(), &[&[TAG_POINTER], &[1], &[TAG_POINTER], &[2]]);
+        let incomplete = envelope(Builder::new(), &[&[TAG_CONTENT_TYPE], b"text/plain", &[TAG_METAPROTOCOL]]);
+        let unrecognized_even = envelope(Builder::new(), &[&[4], b"?", &[], b"body"]);
+        let envelopes = decode(vec![duplicate_pointer, incomplete, unrecognized_even]);
+        assert_eq!(envelopes.len(), 3);
+
+        let duplicate = &envelopes[0].payload;
+        assert!(duplicate.duplicate_field);
+        assert_eq!(duplicate.pointer(), Some(1));
+        // the second pointer is left over, an even tag ord does not understand
+        assert!(duplicate.unrecognized_even_field);
+
+        let incomplete = &envelopes[1].payload;
+        assert!(incomplete.incomplete_field);
+        assert_eq!(incomplete.content_type(), Some("text/plain"));
+        assert_eq!(incomplete.body, None);
+
+        let unrecognized = &envelopes[2].payload;
+        assert!(unrecognized.unrecognized_even_field);
+        assert_eq!(unrecognized.body.as_deref(), Some(b"body".as_slice()));
+        assert_eq!(envelopes.iter().map(|envelope| envelope.input).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn decode_offsets_pushnum_and_stutter() {
+        // two envelopes in one input, the second using a pushnum for its tag
+        let two = envelope(envelope(Builder::new(), &[&[TAG_CONTENT_TYPE], b"a"]), &[]);
+        let two = two
+            .push_opcode(OP_FALSE)
+            .push_opcode(OP_IF)
+            .push_slice(b"ord")
+            .push_opcode(OP_PUSHNUM_1)
+            .push_slice(b"b")
+            .push_opcode(OP_ENDIF);
+        // OP_FALSE OP_FALSE OP_IF "ord" ... is a stuttered envelope
+        let stutter = envelope(Builder::new().push_opcode(OP_FALSE), &[]);
+        let envelopes = decode(vec![two, stutter]);
+        let positions: Vec<(u32, u32)> = envelopes.iter().map(|envelope| (envelope.input, envelope.offset)).collect();
+        assert_eq!(positions, vec![(0, 0), (0, 1), (0, 2), (1, 0)]);
+        assert!(!envelopes[0].pushnum);
+        assert!(envelopes[2].pushnum);
+        assert_eq!(envelopes[2].payload.content_type(), Some("b"));
+        assert!(!envelopes[0].stutter);
+        assert!(envelopes[3].stutter);
+    }
+
+    #[test]
+    fn non_envelopes_are_skipped() {
+        let wrong_protocol = Builder::new()
+            .push_opcode(OP_FALSE)
+            .push_opcode(OP_IF)
+            .push_slice(b"xyz")
+            .push_opcode(OP_ENDIF);
+        let unterminated = push(Builder::new().push_opcode(OP_FALSE).push_opcode(OP_IF), PROTOCOL_ID);
+        let opcode_inside = push(Builder::new().push_opcode(OP_FALSE).push_opcode(OP_IF), PROTOCOL_ID)
+            .push_opcode(OP_CHECKSIG)
+            .push_opcode(OP_ENDIF);
+        assert!(decode(vec![wrong_protocol, unterminated, opcode_inside]).is_empty());
+        // witness without a tapscript
+        let mut tx = tx(vec![]);
+        tx.input.push(TxIn {
+            witness: Witness::from_slice(&[vec![1; 64]]),
+            ..Default::default()
+        });
+        assert!(InscriptionDecoder::new().decode_tx(&tx).is_empty());
+    }
+}
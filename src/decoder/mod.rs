@@ -1 +1,3 @@
 pub mod rune_decode;
+pub mod rune_transfer;
+pub mod inscription_decode;
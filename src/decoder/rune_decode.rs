@@ -1,87 +1,283 @@
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
 use bitcoin::blockdata::transaction::Transaction;
-use ordinals::{Artifact, Etching, Runestone, SpacedRune};
-use crate::models::runes::{EtchingDetails, RuneTransaction, RuneTxDetails};
+use bitcoin::consensus::deserialize;
+use bitcoin::{Block, Network, OutPoint, Psbt};
+use ordinals::{Artifact, Etching, Height, Rune, RuneId, Runestone, SpacedRune};
+use crate::models::runes::{
+    BlockRuneArtifact, BlockRuneCounts, BlockRunes, CommitOutput, DecodedArtifact, DecodedCenotaph, DecodedRunestone,
+    EtchingDetails, EtchingValidity, RuneTxDetails,
+};
+
+/// Why raw transaction data could not be decoded.
+#[derive(Debug)]
+pub enum DecodeTxError {
+    Hex(hex::FromHexError),
+    Consensus(bitcoin::consensus::encode::Error),
+}
+
+impl fmt::Display for DecodeTxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeTxError::Hex(err) => write!(f, "invalid transaction hex: {}", err),
+            DecodeTxError::Consensus(err) => write!(f, "invalid transaction encoding: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for DecodeTxError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeTxError::Hex(err) => Some(err),
+            DecodeTxError::Consensus(err) => Some(err),
+        }
+    }
+}
+
+impl From<hex::FromHexError> for DecodeTxError {
+    fn from(err: hex::FromHexError) -> Self {
+        DecodeTxError::Hex(err)
+    }
+}
+
+impl From<bitcoin::consensus::encode::Error> for DecodeTxError {
+    fn from(err: bitcoin::consensus::encode::Error) -> Self {
+        DecodeTxError::Consensus(err)
+    }
+}
 
 // dervie copy for RuneTransactionDecoder
 #[derive(Debug, Clone)]
 pub struct RuneTransactionDecoder {}
+
+impl Default for RuneTransactionDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RuneTransactionDecoder {
     pub fn new() -> Self {
         RuneTransactionDecoder {}
     }
-    fn process_etching(tx_id: &String, etching: Etching) -> RuneTransaction {
-        let rune_name = etching.rune.unwrap();
-        let spacers = etching.spacers;
-        let spaced_rune = if spacers.is_some() {
-            Some(SpacedRune::new(rune_name, etching.spacers.unwrap()))
-        } else {
-            None
-        };
-        let supply = etching.supply();
-        let rune_name = if spaced_rune.is_some() {
-            spaced_rune.unwrap().to_string()
-        } else {
-            rune_name.to_string()
-        };
-        return RuneTransaction::ETCHING(EtchingDetails {
+    // `position` is the (block height, tx index) of the etching tx, needed to name reserved runes
+    fn process_etching(transaction: &Transaction, tx_id: &String, etching: Etching, position: Option<(u64, u32)>) -> EtchingDetails {
+        let spacers = etching.spacers.unwrap_or_default();
+        let rune = etching
+            .rune
+            .or_else(|| position.map(|(block, tx)| Rune::reserved(block, tx)));
+        EtchingDetails {
             tx_id: tx_id.to_string(),
-            rune_name,
-            supply,
+            rune_name: rune.map(|rune| SpacedRune::new(rune, spacers)),
+            reserved: etching.rune.is_none(),
+            divisibility: etching.divisibility.unwrap_or_default(),
+            premine: etching.premine.unwrap_or_default(),
+            symbol: etching.symbol,
+            spacers,
+            terms: etching.terms,
+            turbo: etching.turbo,
+            supply: etching.supply(),
             mintable: etching.terms.is_some(),
-        });
-    }
-    fn process_runestone(tx_id: &String, rune: Runestone) -> RuneTransaction {
-        if let Some(etching) = rune.etching {
-            return Self::process_etching(tx_id, etching);
+            commitment_input: etching
+                .rune
+                .and_then(|rune| Self::commitment_inputs(transaction, rune).first().copied()),
         }
-        if let Some(mint) = rune.mint {
-            return RuneTransaction::MINT(mint);
+    }
+    fn process_runestone(transaction: &Transaction, tx_id: &String, rune: Runestone, position: Option<(u64, u32)>) -> DecodedRunestone {
+        DecodedRunestone {
+            etching: rune
+                .etching
+                .map(|etching| Self::process_etching(transaction, tx_id, etching, position)),
+            mint: rune.mint,
+            edicts: rune.edicts,
+            pointer: rune.pointer,
         }
-        return RuneTransaction::TRANSFER(rune.edicts);
     }
 
+    /// Decodes the runestone of a tx whose block position is unknown. Reserved rune names
+    /// can't be derived this way, use [`Self::decode_tx_at`] for those.
     pub fn decode_tx(&self, transaction: &Transaction) -> Option<RuneTxDetails> {
-        let rune_stone = Runestone::decipher(transaction);
-        if rune_stone.is_none() {
-            return None;
+        Self::decode(transaction, None)
+    }
+
+    /// Decodes the runestone of the `tx_index`-th tx of block `height`.
+    pub fn decode_tx_at(&self, transaction: &Transaction, height: u64, tx_index: u32) -> Option<RuneTxDetails> {
+        Self::decode(transaction, Some((height, tx_index)))
+    }
+
+    /// Parses a consensus encoded transaction, surrounding whitespace allowed.
+    pub fn parse_tx_hex(hex: &str) -> Result<Transaction, DecodeTxError> {
+        Self::parse_tx_bytes(&hex::decode(hex.trim())?)
+    }
+
+    pub fn parse_tx_bytes(bytes: &[u8]) -> Result<Transaction, DecodeTxError> {
+        Ok(deserialize(bytes)?)
+    }
+
+    /// [`Self::decode_tx`] for a raw transaction in hex.
+    pub fn decode_hex(&self, hex: &str) -> Result<Option<RuneTxDetails>, DecodeTxError> {
+        Ok(self.decode_tx(&Self::parse_tx_hex(hex)?))
+    }
+
+    /// [`Self::decode_tx`] for a consensus encoded transaction.
+    pub fn decode_bytes(&self, bytes: &[u8]) -> Result<Option<RuneTxDetails>, DecodeTxError> {
+        Ok(self.decode_tx(&Self::parse_tx_bytes(bytes)?))
+    }
+
+    /// Decodes the transaction a PSBT will produce, e.g. to check it before signing.
+    /// Finalized input witnesses are used when present, so the commitment input of an
+    /// etching is only known once the PSBT is finalized.
+    pub fn decode_psbt(&self, psbt: &Psbt) -> Option<RuneTxDetails> {
+        self.decode_tx(&psbt.clone().extract_tx_unchecked_fee_rate())
+    }
+
+    /// Decodes every tx of the block mined at `height`, giving etchings the id of their position.
+    pub fn decode_block(&self, block: &Block, height: u64) -> BlockRunes {
+        let mut artifacts = Vec::new();
+        let mut counts = BlockRuneCounts::default();
+        for (tx_index, transaction) in block.txdata.iter().enumerate() {
+            let tx_index = tx_index as u32;
+            let Some(details) = self.decode_tx_at(transaction, height, tx_index) else {
+                continue;
+            };
+            let (etches, mint) = match &details.artifact {
+                DecodedArtifact::Runestone(runestone) => {
+                    if runestone.is_transfer() {
+                        counts.transfers += 1;
+                    }
+                    (runestone.is_etching(), runestone.mint)
+                }
+                DecodedArtifact::Cenotaph(cenotaph) => {
+                    counts.cenotaphs += 1;
+                    (cenotaph.etching.is_some(), cenotaph.mint)
+                }
+            };
+            let etched = etches.then_some(RuneId { block: height, tx: tx_index });
+            if etches {
+                counts.etchings += 1;
+            }
+            if let Some(id) = mint {
+                *counts.mint_attempts.entry(id).or_default() += 1;
+            }
+            artifacts.push(BlockRuneArtifact { tx_index, details, etched });
         }
-        let txid = &transaction.txid().to_string();
-        let rune_stone = rune_stone.unwrap();
-        match rune_stone {
-            Artifact::Runestone(rune) => {
-                let rune_tx = RuneTransactionDecoder::process_runestone(txid, rune);
-                // println!("Processed transaction {} in {:?}", txid, start.elapsed());
-                return Some(RuneTxDetails {
-                    tx_id: txid.to_string(),
-                    rune_tx,
-                });
+        BlockRunes { height, artifacts, counts }
+    }
+
+    /// Inputs whose tapscript pushes `rune`'s commitment, in input order.
+    pub fn commitment_inputs(transaction: &Transaction, rune: Rune) -> Vec<u32> {
+        let commitment = rune.commitment();
+        let mut inputs = Vec::new();
+        for (index, input) in transaction.input.iter().enumerate() {
+            // any script path spend whatever its leaf version, like ord, the lookup tells if it
+            // spent taproot
+            let Some(leaf) = input.witness.taproot_leaf_script() else {
+                continue;
+            };
+            let tapscript = leaf.script;
+            // the extracted script may be invalid, stop at the first bad instruction like ord
+            let commits = tapscript
+                .instructions()
+                .map_while(Result::ok)
+                .filter_map(|instruction| instruction.push_bytes().map(|bytes| bytes.as_bytes() == commitment))
+                .any(|matches| matches);
+            if commits {
+                inputs.push(index as u32);
             }
-            _ => None,
         }
+        inputs
+    }
+
+    /// Check the etching of the tx mined at `height` on `network` the way ord does before
+    /// indexing it. `lookup` gives the confirmation height of the output a commitment input
+    /// spends. `None` when the tx etches nothing. A name that was already etched is reported
+    /// valid, telling it apart needs ord's index.
+    pub fn validate_etching(
+        &self,
+        transaction: &Transaction,
+        network: Network,
+        height: u64,
+        lookup: impl Fn(OutPoint) -> Option<CommitOutput>,
+    ) -> Option<EtchingValidity> {
+        let rune = match Runestone::decipher(transaction)? {
+            Artifact::Runestone(runestone) => runestone.etching?.rune,
+            Artifact::Cenotaph(cenotaph) => Some(cenotaph.etching?),
+        };
+        let Some(rune) = rune else {
+            return Some(EtchingValidity {
+                rune: None,
+                commitment_input: None,
+                commit_height: None,
+                valid: true,
+            });
+        };
+        let mut validity = EtchingValidity {
+            rune: Some(rune),
+            commitment_input: None,
+            commit_height: None,
+            valid: false,
+        };
+        let minimum = Rune::minimum_at_height(network, Height(u32::try_from(height).unwrap_or(u32::MAX)));
+        let name_allowed = !rune.is_reserved() && rune >= minimum;
+        for input in Self::commitment_inputs(transaction, rune) {
+            let output = lookup(transaction.input[input as usize].previous_output);
+            if validity.commitment_input.is_none() {
+                validity.commitment_input = Some(input);
+                validity.commit_height = output.map(|output| output.height);
+            }
+            let Some(output) = output.filter(|output| output.taproot) else {
+                continue;
+            };
+            let confirmations = height.saturating_add(1).saturating_sub(output.height);
+            if confirmations >= u64::from(Runestone::COMMIT_CONFIRMATIONS) {
+                validity.commitment_input = Some(input);
+                validity.commit_height = Some(output.height);
+                validity.valid = name_allowed;
+                break;
+            }
+        }
+        Some(validity)
+    }
+
+    fn decode(transaction: &Transaction, position: Option<(u64, u32)>) -> Option<RuneTxDetails> {
+        let rune_stone = Runestone::decipher(transaction)?;
+        let txid = &transaction.compute_txid().to_string();
+        let artifact = match rune_stone {
+            Artifact::Runestone(rune) => {
+                DecodedArtifact::Runestone(RuneTransactionDecoder::process_runestone(transaction, txid, rune, position))
+            }
+            Artifact::Cenotaph(cenotaph) => DecodedArtifact::Cenotaph(DecodedCenotaph {
+                flaw: cenotaph.flaw,
+                etching: cenotaph.etching,
+                mint: cenotaph.mint,
+            }),
+        };
+        Some(RuneTxDetails {
+            tx_id: txid.to_string(),
+            artifact,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use bitcoin::consensus::deserialize;
-    use hex::decode as hex_decode;
-    use crate::data::transaction::{NON_RUNE_TX, RUNE_BUY_TX, RUNE_REVEAL_TX_HEX, SIGNET_RUNE_TX};
+    use bitcoin::ScriptBuf;
+    use bitcoin::opcodes::all::{OP_PUSHNUM_13, OP_RETURN};
+    use bitcoin::script::Builder;
+    use std::str::FromStr;
 
-    use crate::models::runes::RuneTransaction;
+    use ordinals::{Edict, Flaw, Terms};
+    use crate::data::transaction::{tx_with_outputs, NON_RUNE_TX, RUNE_BUY_TX, RUNE_REVEAL_TX_HEX, SIGNET_RUNE_TX};
 
     use super::*;
 
     #[test]
     fn test_decode_tx_runestone() {
-        let tx_bytes = hex_decode(RUNE_REVEAL_TX_HEX).expect("Invalid hex string");
-        let tx: Transaction = deserialize(&tx_bytes).expect("Failed to deserialize transaction");
-        let rune_tx_details = RuneTransactionDecoder::new().decode_tx(&tx).unwrap();
-        let runestone = rune_tx_details.rune_tx;
-        match runestone {
-            RuneTransaction::ETCHING(etching) => {
-                assert_eq!(etching.rune_name, "HOOOOOOOOTERS");
+        let rune_tx_details = RuneTransactionDecoder::new().decode_hex(RUNE_REVEAL_TX_HEX).unwrap().unwrap();
+        let runestone = rune_tx_details.into_runestone().unwrap();
+        match runestone.etching {
+            Some(etching) => {
+                assert_eq!(etching.rune_name.unwrap().to_string(), "HOOOOOOOOTERS");
             }
             _ => panic!("Expected etching"),
         }
@@ -89,35 +285,30 @@ mod tests {
     #[test]
     fn test_rune_tx_mint() {
         let mint_tx = "02000000000101e1abe66835908ec28bab86af8914ea85458993da13822e326a3bb1c159dfd0090200000000fdffffff0300000000000000000a6a5d0714c0a23314a30222020000000000002251206bda50e97f9e9107d24774e12099e6ef6fb11047f52949e0cae98ae4aa0c8f8676b9000000000000225120528996bda1de76858fdecd34168c331e12a64f415427ec060ae1df72b4aaaafb0140f4def7a7945dbfdeecc285163a794bd624c603261a02a6a87e9ccc6d56ee1c6b9fe8e48c0bd30e540ca17327d6b0be3b215f5b8edee32b824aa42add2a283b7c00000000";
-        let tx_bytes = hex_decode(mint_tx).expect("Invalid hex string");
-        let tx: Transaction = deserialize(&tx_bytes).expect("Failed to deserialize transaction");
-        let rune_tx_details = RuneTransactionDecoder::new().decode_tx(&tx).unwrap();
-        let runestone = rune_tx_details.rune_tx;
-        match runestone {
-            RuneTransaction::MINT(rune_id) => {
+        let rune_tx_details = RuneTransactionDecoder::new().decode_hex(mint_tx).unwrap().unwrap();
+        let runestone = rune_tx_details.into_runestone().unwrap();
+        match runestone.mint {
+            Some(rune_id) => {
                 assert_eq!(rune_id.block, 840000);
                 assert_eq!(rune_id.tx, 291);
             }
             _ => panic!("Expected minted rune"),
         }
+        assert!(!runestone.is_etching());
     }
     #[test]
     fn test_decode_tx_non_rune_tx() {
-        let tx_bytes = hex_decode(NON_RUNE_TX).expect("Invalid hex string");
-        let tx: Transaction = deserialize(&tx_bytes).expect("Failed to deserialize transaction");
-        let rune_tx_details = RuneTransactionDecoder::new().decode_tx(&tx);
+        let rune_tx_details = RuneTransactionDecoder::new().decode_hex(NON_RUNE_TX).unwrap();
         assert!(rune_tx_details.is_none());
     }
 
     #[test]
     fn test_decode_signet_rune_tx() {
-        let tx_bytes = hex_decode(SIGNET_RUNE_TX).expect("Invalid hex string");
-        let tx: Transaction = deserialize(&tx_bytes).expect("Failed to deserialize transaction");
-        let rune_tx_details = RuneTransactionDecoder::new().decode_tx(&tx).unwrap();
-        let runestone = rune_tx_details.rune_tx;
-        match runestone {
-            RuneTransaction::ETCHING(etching) => {
-                assert_eq!(etching.rune_name, "MAOBY•THE•CUTEST•CAT");
+        let rune_tx_details = RuneTransactionDecoder::new().decode_hex(SIGNET_RUNE_TX).unwrap().unwrap();
+        let runestone = rune_tx_details.into_runestone().unwrap();
+        match runestone.etching {
+            Some(etching) => {
+                assert_eq!(etching.rune_name.unwrap().to_string(), "MAOBY•THE•CUTEST•CAT");
             }
             _ => panic!("Expected minted rune"),
         }
@@ -125,17 +316,350 @@ mod tests {
 
     #[test]
     fn test_decode_sell_tx() {
-        let tx_bytes = hex_decode(RUNE_BUY_TX).expect("Invalid hex string");
-        let tx: Transaction = deserialize(&tx_bytes).expect("Failed to deserialize transaction");
-        let rune_tx_details = RuneTransactionDecoder::new().decode_tx(&tx).unwrap();
-        let runestone = rune_tx_details.rune_tx;
-        match runestone {
-            RuneTransaction::TRANSFER(edicts) => {
-                assert_eq!(edicts.len(), 1);
-                assert_eq!(edicts.first().unwrap().id.block, 840010);
-                assert_eq!(edicts.first().unwrap().id.tx, 4);
-            }
-            _ => panic!("Expected transfer rune"),
+        let rune_tx_details = RuneTransactionDecoder::new().decode_hex(RUNE_BUY_TX).unwrap().unwrap();
+        let runestone = rune_tx_details.into_runestone().unwrap();
+        assert!(runestone.is_transfer(), "Expected transfer rune");
+        let edicts = runestone.edicts;
+        assert_eq!(edicts.len(), 1);
+        assert_eq!(edicts.first().unwrap().id.block, 840010);
+        assert_eq!(edicts.first().unwrap().id.tx, 4);
+    }
+
+    #[test]
+    fn test_decode_mint_with_edicts_and_pointer() {
+        let rune_id = RuneId { block: 840000, tx: 3 };
+        let runestone = Runestone {
+            edicts: vec![
+                Edict { id: rune_id, amount: 100, output: 1 },
+                Edict { id: RuneId { block: 840010, tx: 4 }, amount: 0, output: 2 },
+            ],
+            mint: Some(rune_id),
+            pointer: Some(2),
+            ..Default::default()
+        };
+        let tx = tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new(), ScriptBuf::new()]);
+        let decoded = RuneTransactionDecoder::new().decode_tx(&tx).unwrap().into_runestone().unwrap();
+        assert!(decoded.is_mint());
+        assert!(decoded.is_transfer());
+        assert!(!decoded.is_etching());
+        assert_eq!(decoded.mint, Some(rune_id));
+        assert_eq!(decoded.edicts, runestone.edicts);
+        assert_eq!(decoded.pointer, Some(2));
+    }
+
+    #[test]
+    fn test_decode_etching_keeps_edicts() {
+        let tx = RuneTransactionDecoder::parse_tx_hex(RUNE_REVEAL_TX_HEX).unwrap();
+        let decoded = RuneTransactionDecoder::new().decode_tx(&tx).unwrap().into_runestone().unwrap();
+        let Some(Artifact::Runestone(expected)) = Runestone::decipher(&tx) else {
+            panic!("Expected runestone");
+        };
+        assert!(decoded.is_etching());
+        assert_eq!(decoded.edicts, expected.edicts);
+        assert_eq!(decoded.mint, expected.mint);
+        assert_eq!(decoded.pointer, expected.pointer);
+    }
+
+    #[test]
+    fn test_decode_cenotaph_with_flaw() {
+        // an edict pointing at an output that does not exist makes the runestone a cenotaph
+        let runestone = Runestone {
+            edicts: vec![Edict { id: RuneId { block: 840000, tx: 3 }, amount: 1, output: 5 }],
+            mint: Some(RuneId { block: 840000, tx: 3 }),
+            ..Default::default()
+        };
+        let tx = tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()]);
+        let details = RuneTransactionDecoder::new().decode_tx(&tx).unwrap();
+        assert!(details.is_cenotaph());
+        assert!(details.runestone().is_none());
+        let cenotaph = details.cenotaph().unwrap();
+        assert_eq!(cenotaph.flaw, Some(Flaw::EdictOutput));
+        assert_eq!(cenotaph.mint, Some(RuneId { block: 840000, tx: 3 }));
+        assert_eq!(cenotaph.etching, None);
+    }
+
+    #[test]
+    fn test_decode_cenotaph_keeps_etched_rune() {
+        // tag 2 (flags) with the etching bit, tag 4 (rune) and an unrecognized even tag 126
+        let script = Builder::new()
+            .push_opcode(OP_RETURN)
+            .push_opcode(OP_PUSHNUM_13)
+            .push_slice([2, 1, 4, 100, 126, 0])
+            .into_script();
+        let tx = tx_with_outputs(vec![script]);
+        let details = RuneTransactionDecoder::new().decode_tx(&tx).unwrap();
+        let cenotaph = details.cenotaph().unwrap();
+        assert_eq!(cenotaph.flaw, Some(Flaw::UnrecognizedEvenTag));
+        assert_eq!(cenotaph.etching, Some(Rune(100)));
+    }
+
+    #[test]
+    fn test_decode_etching_parameters() {
+        let tx = RuneTransactionDecoder::parse_tx_hex(RUNE_REVEAL_TX_HEX).unwrap();
+        let Some(Artifact::Runestone(Runestone { etching: Some(expected), .. })) = Runestone::decipher(&tx) else {
+            panic!("Expected etching");
+        };
+        let decoded = RuneTransactionDecoder::new().decode_tx(&tx).unwrap().into_runestone().unwrap();
+        let etching = decoded.etching.unwrap();
+        assert!(!etching.reserved);
+        assert_eq!(etching.rune_name.unwrap().rune, expected.rune.unwrap());
+        assert_eq!(etching.divisibility, expected.divisibility.unwrap_or_default());
+        assert_eq!(etching.premine, expected.premine.unwrap_or_default());
+        assert_eq!(etching.symbol, expected.symbol);
+        assert_eq!(etching.spacers, expected.spacers.unwrap_or_default());
+        assert_eq!(etching.terms, expected.terms);
+        assert_eq!(etching.turbo, expected.turbo);
+    }
+
+    #[test]
+    fn test_decode_reserved_etching() {
+        let terms = Terms {
+            amount: Some(1000),
+            cap: Some(21),
+            height: (Some(840100), None),
+            offset: (None, Some(5000)),
+        };
+        let runestone = Runestone {
+            etching: Some(Etching {
+                divisibility: Some(2),
+                premine: Some(500),
+                symbol: Some('$'),
+                terms: Some(terms),
+                turbo: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let tx = tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()]);
+
+        let unplaced = RuneTransactionDecoder::new().decode_tx(&tx).unwrap().into_runestone().unwrap();
+        let etching = unplaced.etching.unwrap();
+        assert!(etching.reserved);
+        assert_eq!(etching.rune_name, None);
+
+        let placed = RuneTransactionDecoder::new().decode_tx_at(&tx, 840000, 7).unwrap().into_runestone().unwrap();
+        let etching = placed.etching.unwrap();
+        assert!(etching.reserved);
+        assert_eq!(etching.rune_name.unwrap().rune, Rune::reserved(840000, 7));
+        assert_eq!(etching.divisibility, 2);
+        assert_eq!(etching.premine, 500);
+        assert_eq!(etching.symbol, Some('$'));
+        assert_eq!(etching.terms, Some(terms));
+        assert!(etching.turbo);
+        assert_eq!(etching.supply, Some(500 + 1000 * 21));
+        assert!(etching.mintable);
+    }
+
+    fn commit_input(rune: Rune) -> bitcoin::TxIn {
+        let commitment = bitcoin::script::PushBytesBuf::try_from(rune.commitment()).unwrap();
+        let tapscript = Builder::new()
+            .push_slice(commitment)
+            .push_opcode(bitcoin::opcodes::all::OP_DROP)
+            .push_slice([2; 32])
+            .push_opcode(bitcoin::opcodes::all::OP_CHECKSIG)
+            .into_script();
+        bitcoin::TxIn {
+            witness: bitcoin::Witness::from_slice(&[vec![1; 64], tapscript.into_bytes(), vec![0xc0; 33]]),
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn test_etching_commitment_input() {
+        let tx = RuneTransactionDecoder::parse_tx_hex(RUNE_REVEAL_TX_HEX).unwrap();
+        let decoded = RuneTransactionDecoder::new().decode_tx(&tx).unwrap().into_runestone().unwrap();
+        assert_eq!(decoded.etching.unwrap().commitment_input, Some(0));
+    }
+
+    #[test]
+    fn test_validate_etching() {
+        let rune = Rune::from_str("UNCOMMONGOODS").unwrap();
+        let runestone = Runestone {
+            etching: Some(Etching { rune: Some(rune), ..Default::default() }),
+            ..Default::default()
+        };
+        let mut tx = tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()]);
+        tx.input = vec![bitcoin::TxIn::default(), commit_input(rune)];
+        let decoder = RuneTransactionDecoder::new();
+        assert_eq!(RuneTransactionDecoder::commitment_inputs(&tx, rune), vec![1]);
+        assert_eq!(RuneTransactionDecoder::commitment_inputs(&tx, Rune(rune.n() + 1)), Vec::<u32>::new());
+
+        let committed_at = |height, taproot| move |_| Some(CommitOutput { height, taproot });
+        // the commit output is the sixth confirmation at the etching height
+        let validity = decoder.validate_etching(&tx, Network::Bitcoin, 840005, committed_at(840000, true)).unwrap();
+        assert_eq!(validity.rune, Some(rune));
+        assert_eq!(validity.commitment_input, Some(1));
+        assert_eq!(validity.commit_height, Some(840000));
+        assert!(validity.valid);
+        assert!(!decoder.validate_etching(&tx, Network::Bitcoin, 840004, committed_at(840000, true)).unwrap().valid);
+        assert!(!decoder.validate_etching(&tx, Network::Bitcoin, 840005, committed_at(840000, false)).unwrap().valid);
+        let unknown = decoder.validate_etching(&tx, Network::Bitcoin, 840005, |_| None).unwrap();
+        assert!(!unknown.valid);
+        assert_eq!(unknown.commitment_input, Some(1));
+        assert_eq!(unknown.commit_height, None);
+
+        // the commitment has to be for the etched name
+        tx.input[1] = commit_input(Rune(rune.n() + 1));
+        let validity = decoder.validate_etching(&tx, Network::Bitcoin, 840005, committed_at(840000, true)).unwrap();
+        assert_eq!(validity.commitment_input, None);
+        assert!(!validity.valid);
+        let decoded = decoder.decode_tx(&tx).unwrap().into_runestone().unwrap();
+        assert_eq!(decoded.etching.unwrap().commitment_input, None);
+    }
+
+    #[test]
+    fn test_validate_etching_name_rules() {
+        let committed = |_| Some(CommitOutput { height: 840000, taproot: true });
+        let etch = |rune: Rune| {
+            let runestone = Runestone {
+                etching: Some(Etching { rune: Some(rune), ..Default::default() }),
+                ..Default::default()
+            };
+            let mut tx = tx_with_outputs(vec![runestone.encipher()]);
+            tx.input = vec![commit_input(rune)];
+            tx
+        };
+        let decoder = RuneTransactionDecoder::new();
+
+        // twelve letters are only unlocked four months after the halving
+        let short = Rune::from_str("UNCOMMONGOOD").unwrap();
+        let validity = decoder.validate_etching(&etch(short), Network::Bitcoin, 840005, committed).unwrap();
+        assert_eq!(validity.commitment_input, Some(0));
+        assert!(!validity.valid);
+        assert!(decoder.validate_etching(&etch(short), Network::Bitcoin, 857500, committed).unwrap().valid);
+
+        // reserved names can't be etched explicitly
+        let reserved = Rune::reserved(840000, 1);
+        assert!(!decoder.validate_etching(&etch(reserved), Network::Bitcoin, 840005, committed).unwrap().valid);
+
+        // the height doesn't overflow
+        let name = Rune::from_str("A").unwrap();
+        assert!(decoder.validate_etching(&etch(name), Network::Bitcoin, u64::MAX, committed).unwrap().valid);
+    }
+
+    #[test]
+    fn test_validate_reserved_and_missing_etching() {
+        let reserved = Runestone {
+            etching: Some(Etching::default()),
+            ..Default::default()
+        };
+        let tx = tx_with_outputs(vec![reserved.encipher(), ScriptBuf::new()]);
+        let validity = RuneTransactionDecoder::new().validate_etching(&tx, Network::Bitcoin, 840000, |_| None).unwrap();
+        assert_eq!(validity.rune, None);
+        assert!(validity.valid);
+
+        let mint = tx_with_outputs(vec![Runestone::default().encipher()]);
+        assert!(RuneTransactionDecoder::new().validate_etching(&mint, Network::Bitcoin, 840000, |_| None).is_none());
+    }
+
+    #[test]
+    fn test_decode_block() {
+        use bitcoin::block::{Header, Version as BlockVersion};
+        use bitcoin::hashes::Hash;
+        use bitcoin::{BlockHash, CompactTarget, TxMerkleNode};
+
+        let decode = |hex| RuneTransactionDecoder::parse_tx_hex(hex).unwrap();
+        let minted = RuneId { block: 840000, tx: 3 };
+        let mint = |edicts| {
+            let runestone = Runestone { mint: Some(minted), edicts, ..Default::default() };
+            tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()])
+        };
+        let cenotaph = {
+            let runestone = Runestone {
+                edicts: vec![Edict { id: minted, amount: 1, output: 5 }],
+                mint: Some(minted),
+                ..Default::default()
+            };
+            tx_with_outputs(vec![runestone.encipher()])
+        };
+        let reserved = {
+            let runestone = Runestone { etching: Some(Etching::default()), ..Default::default() };
+            tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()])
+        };
+        let block = Block {
+            header: Header {
+                version: BlockVersion::ONE,
+                prev_blockhash: BlockHash::all_zeros(),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: 0,
+                bits: CompactTarget::from_consensus(0),
+                nonce: 0,
+            },
+            txdata: vec![
+                decode(NON_RUNE_TX),
+                decode(RUNE_REVEAL_TX_HEX),
+                mint(vec![]),
+                mint(vec![Edict { id: minted, amount: 0, output: 1 }]),
+                cenotaph,
+                decode(RUNE_BUY_TX),
+                reserved,
+            ],
+        };
+        let decoded = RuneTransactionDecoder::new().decode_block(&block, 850000);
+        assert_eq!(decoded.height, 850000);
+        let indices: Vec<u32> = decoded.artifacts.iter().map(|artifact| artifact.tx_index).collect();
+        assert_eq!(indices, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(decoded.artifacts[0].etched, Some(RuneId { block: 850000, tx: 1 }));
+        assert_eq!(decoded.artifacts[1].etched, None);
+        assert!(decoded.artifacts[3].details.is_cenotaph());
+        let reserved = &decoded.artifacts[5];
+        assert_eq!(reserved.etched, Some(RuneId { block: 850000, tx: 6 }));
+        let etching = reserved.details.runestone().unwrap().etching.as_ref().unwrap();
+        assert_eq!(etching.rune_name.unwrap().rune, Rune::reserved(850000, 6));
+
+        assert_eq!(decoded.counts.etchings, 2);
+        assert_eq!(decoded.counts.mint_attempts.get(&minted), Some(&3));
+        assert_eq!(decoded.counts.transfers, 2);
+        assert_eq!(decoded.counts.cenotaphs, 1);
+    }
+
+    #[test]
+    fn test_decode_bad_raw_tx() {
+        let decoder = RuneTransactionDecoder::new();
+        assert!(matches!(decoder.decode_hex("not hex"), Err(DecodeTxError::Hex(_))));
+        assert!(matches!(decoder.decode_hex("0200"), Err(DecodeTxError::Consensus(_))));
+        let mut bytes = hex::decode(RUNE_BUY_TX).unwrap();
+        assert!(decoder.decode_bytes(&bytes).unwrap().is_some());
+        // trailing data is not a valid encoding either
+        bytes.push(0);
+        let err = decoder.decode_bytes(&bytes).unwrap_err();
+        assert!(err.to_string().starts_with("invalid transaction encoding"));
+        let padded = format!("  {}\n", RUNE_BUY_TX);
+        assert!(decoder.decode_hex(&padded).unwrap().unwrap().runestone().unwrap().is_transfer());
+    }
+
+    #[test]
+    fn test_decode_unsigned_psbt() {
+        let rune_id = RuneId { block: 840000, tx: 3 };
+        let runestone = Runestone {
+            edicts: vec![Edict { id: rune_id, amount: 10, output: 1 }],
+            mint: Some(rune_id),
+            ..Default::default()
+        };
+        let mut tx = tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()]);
+        tx.input = vec![bitcoin::TxIn::default()];
+        let psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        let decoded = RuneTransactionDecoder::new().decode_psbt(&psbt).unwrap().into_runestone().unwrap();
+        assert_eq!(decoded.mint, Some(rune_id));
+        assert_eq!(decoded.edicts, runestone.edicts);
+    }
+
+    #[test]
+    fn test_decode_finalized_psbt_uses_witness() {
+        let rune = Rune::from_str("UNCOMMONGOODS").unwrap();
+        let runestone = Runestone {
+            etching: Some(Etching { rune: Some(rune), ..Default::default() }),
+            ..Default::default()
+        };
+        let mut tx = tx_with_outputs(vec![runestone.encipher(), ScriptBuf::new()]);
+        tx.input = vec![bitcoin::TxIn::default()];
+        let mut psbt = Psbt::from_unsigned_tx(tx).unwrap();
+        let decoder = RuneTransactionDecoder::new();
+        let unsigned = decoder.decode_psbt(&psbt).unwrap().into_runestone().unwrap();
+        assert_eq!(unsigned.etching.unwrap().commitment_input, None);
+
+        psbt.inputs[0].final_script_witness = Some(commit_input(rune).witness);
+        let finalized = decoder.decode_psbt(&psbt).unwrap().into_runestone().unwrap();
+        assert_eq!(finalized.etching.unwrap().commitment_input, Some(0));
+    }
 }
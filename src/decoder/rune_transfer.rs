@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+
+use bitcoin::blockdata::transaction::Transaction;
+use ordinals::{Edict, RuneId};
+
+use crate::data::rune_entry::MintStatus;
+use crate::decoder::rune_decode::RuneTransactionDecoder;
+use crate::models::runes::DecodedArtifact;
+
+/// Rune balances in base units, keyed by rune id.
+pub type RuneBalances = BTreeMap<RuneId, u128>;
+
+/// What the index knows about a tx besides its inputs: which id an etching gets and how
+/// much a mint creates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferContext {
+    /// Id of the rune the tx etches, `RuneId { block: height, tx: index }`. `None` when the
+    /// tx etches nothing or the etching is invalid (name taken, too short, not committed).
+    pub etched: Option<RuneId>,
+    /// Base units the mint creates, `None` when the tx mints nothing or the mint is closed.
+    pub mint_amount: Option<u128>,
+}
+
+impl TransferContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn etched(mut self, id: RuneId) -> Self {
+        self.etched = Some(id);
+        self
+    }
+
+    /// Mint amount from the status of the minted rune at the tx's height.
+    pub fn mint(mut self, status: &MintStatus) -> Self {
+        self.mint_amount = status.is_open().then_some(status.amount_per_mint);
+        self
+    }
+}
+
+/// Rune balances after a tx: what each output holds and what was burned.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TransferOutcome {
+    /// One entry per tx output, empty for outputs receiving no runes. OP_RETURN outputs
+    /// never hold runes, whatever is sent to them is burned.
+    pub outputs: Vec<RuneBalances>,
+    pub burned: RuneBalances,
+}
+
+impl RuneTransactionDecoder {
+    /// Apply the tx's runestone to the rune balances of its inputs, following ord's
+    /// allocation rules: mint and premine join the inputs, edicts allocate in order,
+    /// what is left goes to the pointer or the first non-OP_RETURN output, and a cenotaph
+    /// burns everything.
+    pub fn simulate_transfer(
+        &self,
+        transaction: &Transaction,
+        inputs: &[RuneBalances],
+        context: TransferContext,
+    ) -> TransferOutcome {
+        let mut unallocated = RuneBalances::new();
+        for (id, amount) in inputs.iter().flatten() {
+            add(&mut unallocated, *id, *amount);
+        }
+        let mut allocated = vec![RuneBalances::new(); transaction.output.len()];
+        let mut burned = RuneBalances::new();
+
+        let artifact = self.decode_tx(transaction).map(|details| details.artifact);
+        let mint = match &artifact {
+            Some(DecodedArtifact::Runestone(runestone)) => runestone.mint,
+            Some(DecodedArtifact::Cenotaph(cenotaph)) => cenotaph.mint,
+            None => None,
+        };
+        if let (Some(id), Some(amount)) = (mint, context.mint_amount) {
+            add(&mut unallocated, id, amount);
+        }
+
+        match &artifact {
+            Some(DecodedArtifact::Cenotaph(_)) => {
+                for (id, amount) in unallocated {
+                    add(&mut burned, id, amount);
+                }
+            }
+            _ => {
+                let runestone = match artifact {
+                    Some(DecodedArtifact::Runestone(runestone)) => Some(runestone),
+                    _ => None,
+                };
+                if let (Some(runestone), Some(id)) = (&runestone, context.etched) {
+                    let premine = runestone.etching.as_ref().map(|etching| etching.premine);
+                    add(&mut unallocated, id, premine.unwrap_or_default());
+                }
+                for edict in runestone.iter().flat_map(|runestone| &runestone.edicts) {
+                    allocate_edict(transaction, edict, context.etched, &mut unallocated, &mut allocated);
+                }
+                let pointer = runestone
+                    .and_then(|runestone| runestone.pointer)
+                    .map(|pointer| pointer as usize)
+                    .filter(|pointer| *pointer < transaction.output.len());
+                let default_output = pointer.or_else(|| {
+                    transaction
+                        .output
+                        .iter()
+                        .position(|tx_out| !tx_out.script_pubkey.is_op_return())
+                });
+                let destination = match default_output {
+                    Some(vout) => &mut allocated[vout],
+                    None => &mut burned,
+                };
+                for (id, amount) in unallocated {
+                    add(destination, id, amount);
+                }
+            }
+        }
+
+        for (vout, balances) in allocated.iter_mut().enumerate() {
+            if transaction.output[vout].script_pubkey.is_op_return() {
+                for (id, amount) in std::mem::take(balances) {
+                    add(&mut burned, id, amount);
+                }
+            }
+        }
+        TransferOutcome {
+            outputs: allocated,
+            burned,
+        }
+    }
+}
+
+fn allocate_edict(
+    transaction: &Transaction,
+    edict: &Edict,
+    etched: Option<RuneId>,
+    unallocated: &mut RuneBalances,
+    allocated: &mut [RuneBalances],
+) {
+    // id 0:0 refers to the rune etched by this same tx
+    let id = if edict.id == RuneId::default() {
+        match etched {
+            Some(id) => id,
+            None => return,
+        }
+    } else {
+        edict.id
+    };
+    let Some(balance) = unallocated.get_mut(&id) else {
+        return;
+    };
+    let mut allocate = |balance: &mut u128, amount: u128, output: usize| {
+        if amount > 0 {
+            *balance -= amount;
+            add(&mut allocated[output], id, amount);
+        }
+    };
+    let output = edict.output as usize;
+    if output == transaction.output.len() {
+        // an edict to output n splits across every non-OP_RETURN output
+        let destinations = transaction
+            .output
+            .iter()
+            .enumerate()
+            .filter(|(_, tx_out)| !tx_out.script_pubkey.is_op_return())
+            .map(|(vout, _)| vout)
+            .collect::<Vec<usize>>();
+        if destinations.is_empty() {
+            return;
+        }
+        if edict.amount == 0 {
+            let share = *balance / destinations.len() as u128;
+            let remainder = (*balance % destinations.len() as u128) as usize;
+            for (i, vout) in destinations.into_iter().enumerate() {
+                let amount = if i < remainder { share + 1 } else { share };
+                allocate(balance, amount, vout);
+            }
+        } else {
+            for vout in destinations {
+                let amount = edict.amount.min(*balance);
+                allocate(balance, amount, vout);
+            }
+        }
+    } else if output < transaction.output.len() {
+        let amount = if edict.amount == 0 {
+            *balance
+        } else {
+            edict.amount.min(*balance)
+        };
+        allocate(balance, amount, output);
+    }
+}
+
+// caller supplied input balances can sum past u128::MAX, keep the maximum rather than panic
+fn add(balances: &mut RuneBalances, id: RuneId, amount: u128) {
+    if amount > 0 {
+        let balance = balances.entry(id).or_default();
+        *balance = balance.saturating_add(amount);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::ScriptBuf;
+    use ordinals::{Etching, Rune, Runestone};
+
+    use super::*;
+    use crate::data::transaction::tx_with_outputs;
+
+    const RUNE: RuneId = RuneId { block: 840000, tx: 3 };
+    const OTHER: RuneId = RuneId { block: 840010, tx: 4 };
+
+    fn tx(runestone: Option<&Runestone>, outputs: usize) -> Transaction {
+        let mut output = runestone
+            .map(|runestone| vec![runestone.encipher()])
+            .unwrap_or_default();
+        output.extend((0..outputs).map(|_| ScriptBuf::new()));
+        tx_with_outputs(output)
+    }
+
+    fn balances(entries: &[(RuneId, u128)]) -> RuneBalances {
+        entries.iter().copied().collect()
+    }
+
+    fn simulate(tx: &Transaction, inputs: &[RuneBalances], context: TransferContext) -> TransferOutcome {
+        RuneTransactionDecoder::new().simulate_transfer(tx, inputs, context)
+    }
+
+    #[test]
+    fn no_runestone_goes_to_first_non_op_return_output() {
+        let tx = tx(None, 2);
+        let outcome = simulate(&tx, &[balances(&[(RUNE, 10)]), balances(&[(RUNE, 5), (OTHER, 1)])], TransferContext::new());
+        assert_eq!(outcome.outputs, vec![balances(&[(RUNE, 15), (OTHER, 1)]), RuneBalances::new()]);
+        assert!(outcome.burned.is_empty());
+    }
+
+    #[test]
+    fn edicts_then_pointer() {
+        let runestone = Runestone {
+            edicts: vec![
+                Edict { id: RUNE, amount: 4, output: 1 },
+                Edict { id: RUNE, amount: 100, output: 2 },
+                Edict { id: OTHER, amount: 1, output: 1 },
+            ],
+            pointer: Some(3),
+            ..Default::default()
+        };
+        let tx = tx(Some(&runestone), 3);
+        let outcome = simulate(&tx, &[balances(&[(RUNE, 10), (OTHER, 3)])], TransferContext::new());
+        assert_eq!(
+            outcome.outputs,
+            vec![
+                RuneBalances::new(),
+                balances(&[(RUNE, 4), (OTHER, 1)]),
+                balances(&[(RUNE, 6)]),
+                balances(&[(OTHER, 2)]),
+            ]
+        );
+        assert!(outcome.burned.is_empty());
+    }
+
+    #[test]
+    fn edict_to_output_count_splits_across_outputs() {
+        // amount 0 divides the balance evenly, the first outputs take the remainder
+        let split = Runestone {
+            edicts: vec![Edict { id: RUNE, amount: 0, output: 4 }],
+            ..Default::default()
+        };
+        let outcome = simulate(&tx(Some(&split), 3), &[balances(&[(RUNE, 11)])], TransferContext::new());
+        assert_eq!(
+            outcome.outputs,
+            vec![
+                RuneBalances::new(),
+                balances(&[(RUNE, 4)]),
+                balances(&[(RUNE, 4)]),
+                balances(&[(RUNE, 3)]),
+            ]
+        );
+
+        // a non-zero amount is given to each output while the balance lasts
+        let each = Runestone {
+            edicts: vec![Edict { id: RUNE, amount: 5, output: 4 }],
+            ..Default::default()
+        };
+        let outcome = simulate(&tx(Some(&each), 3), &[balances(&[(RUNE, 12)])], TransferContext::new());
+        assert_eq!(
+            outcome.outputs,
+            vec![
+                RuneBalances::new(),
+                balances(&[(RUNE, 5)]),
+                balances(&[(RUNE, 5)]),
+                balances(&[(RUNE, 2)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn op_return_outputs_burn() {
+        let runestone = Runestone {
+            edicts: vec![Edict { id: RUNE, amount: 3, output: 0 }],
+            ..Default::default()
+        };
+        let outcome = simulate(&tx(Some(&runestone), 1), &[balances(&[(RUNE, 10)])], TransferContext::new());
+        assert_eq!(outcome.burned, balances(&[(RUNE, 3)]));
+        assert_eq!(outcome.outputs, vec![RuneBalances::new(), balances(&[(RUNE, 7)])]);
+
+        // nothing but OP_RETURN outputs: unallocated runes are burned
+        let only_op_return = tx(Some(&Runestone::default()), 0);
+        let outcome = simulate(&only_op_return, &[balances(&[(RUNE, 10)])], TransferContext::new());
+        assert_eq!(outcome.burned, balances(&[(RUNE, 10)]));
+    }
+
+    #[test]
+    fn mint_and_etching_join_the_inputs() {
+        let etched = RuneId { block: 840100, tx: 2 };
+        let runestone = Runestone {
+            etching: Some(Etching {
+                rune: Some(Rune(99246114928149462)),
+                premine: Some(1000),
+                ..Default::default()
+            }),
+            edicts: vec![Edict { id: RuneId::default(), amount: 400, output: 2 }],
+            mint: Some(RUNE),
+            ..Default::default()
+        };
+        let tx = tx(Some(&runestone), 2);
+        let context = TransferContext {
+            etched: Some(etched),
+            mint_amount: Some(50),
+        };
+        let outcome = simulate(&tx, &[], context);
+        assert_eq!(
+            outcome.outputs,
+            vec![RuneBalances::new(), balances(&[(RUNE, 50), (etched, 600)]), balances(&[(etched, 400)])]
+        );
+
+        // a closed mint or an invalid etching creates nothing
+        let outcome = simulate(&tx, &[], TransferContext::new());
+        assert_eq!(outcome.outputs, vec![RuneBalances::new(); 3]);
+    }
+
+    #[test]
+    fn cenotaph_burns_inputs_and_mint() {
+        // an edict to an output that does not exist makes the runestone a cenotaph
+        let runestone = Runestone {
+            edicts: vec![Edict { id: RUNE, amount: 1, output: 5 }],
+            mint: Some(RUNE),
+            ..Default::default()
+        };
+        let tx = tx(Some(&runestone), 2);
+        let context = TransferContext {
+            etched: None,
+            mint_amount: Some(50),
+        };
+        let outcome = simulate(&tx, &[balances(&[(RUNE, 10), (OTHER, 1)])], context);
+        assert_eq!(outcome.burned, balances(&[(RUNE, 60), (OTHER, 1)]));
+        assert_eq!(outcome.outputs, vec![RuneBalances::new(); 3]);
+    }
+}
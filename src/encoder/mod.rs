@@ -0,0 +1 @@
+pub mod runestone_builder;
@@ -0,0 +1,288 @@
+use std::fmt;
+
+use bitcoin::{Amount, ScriptBuf, TxOut};
+use ordinals::{Edict, Etching, Rune, RuneId, Runestone, SpacedRune, Terms};
+
+/// Runestone parameters ord would reject.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildRunestoneError {
+    DivisibilityTooHigh(u8),
+    ReservedName(Rune),
+    /// A spacer past the last letter of the name.
+    SpacersOutOfRange { rune: Rune, spacers: u32 },
+    SpacersWithoutName,
+    /// Premine plus cap times amount does not fit in a `u128`.
+    SupplyOverflow,
+    /// Block 0 only refers to the rune etched by the same tx, as `0:0`.
+    InvalidMintId(RuneId),
+}
+
+impl fmt::Display for BuildRunestoneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildRunestoneError::DivisibilityTooHigh(divisibility) => write!(
+                f,
+                "divisibility {} is above the maximum of {}",
+                divisibility,
+                Etching::MAX_DIVISIBILITY
+            ),
+            BuildRunestoneError::ReservedName(rune) => write!(f, "rune name {} is reserved", rune),
+            BuildRunestoneError::SpacersOutOfRange { rune, spacers } => {
+                write!(f, "spacers {:#b} do not fit rune {}", spacers, rune)
+            }
+            BuildRunestoneError::SpacersWithoutName => write!(f, "spacers need a rune name"),
+            BuildRunestoneError::SupplyOverflow => write!(f, "premine plus cap times amount overflows"),
+            BuildRunestoneError::InvalidMintId(id) => write!(f, "invalid mint rune id {}", id),
+        }
+    }
+}
+
+impl std::error::Error for BuildRunestoneError {}
+
+/// Builds the OP_RETURN output of a runestone that etches, mints and/or transfers.
+#[derive(Debug, Default)]
+pub struct RunestoneBuilder {
+    runestone: Runestone,
+}
+
+impl RunestoneBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Etch a rune with the given name, spacers included.
+    pub fn etch(mut self, spaced_rune: SpacedRune) -> Self {
+        let etching = self.etching();
+        etching.rune = Some(spaced_rune.rune);
+        etching.spacers = (spaced_rune.spacers != 0).then_some(spaced_rune.spacers);
+        self
+    }
+
+    /// Etch a rune without a name, it gets the reserved name of the tx's block position.
+    pub fn etch_reserved(mut self) -> Self {
+        self.etching();
+        self
+    }
+
+    pub fn divisibility(mut self, divisibility: u8) -> Self {
+        self.etching().divisibility = Some(divisibility);
+        self
+    }
+
+    pub fn symbol(mut self, symbol: char) -> Self {
+        self.etching().symbol = Some(symbol);
+        self
+    }
+
+    /// Base units of the etched rune allocated to the etching tx itself.
+    pub fn premine(mut self, premine: u128) -> Self {
+        self.etching().premine = Some(premine);
+        self
+    }
+
+    /// Open mint terms: amount per mint, cap, and the absolute and relative height ranges.
+    pub fn terms(mut self, terms: Terms) -> Self {
+        self.etching().terms = Some(terms);
+        self
+    }
+
+    pub fn turbo(mut self, turbo: bool) -> Self {
+        self.etching().turbo = turbo;
+        self
+    }
+
+    pub fn mint(mut self, id: RuneId) -> Self {
+        self.runestone.mint = Some(id);
+        self
+    }
+
+    /// Send `amount` base units of `id` to `output`. Amount 0 sends the whole remaining
+    /// balance and output `n` (the number of outputs) splits across all non-OP_RETURN
+    /// outputs. `RuneId::default()` refers to the rune etched by the same tx.
+    ///
+    /// Edicts are encoded sorted by rune id, edicts of the same rune keep their order.
+    pub fn edict(mut self, id: RuneId, amount: u128, output: u32) -> Self {
+        self.runestone.edicts.push(Edict { id, amount, output });
+        self
+    }
+
+    /// Output receiving the runes no edict allocates, instead of the first non-OP_RETURN output.
+    pub fn pointer(mut self, output: u32) -> Self {
+        self.runestone.pointer = Some(output);
+        self
+    }
+
+    /// The runestone, checked for parameters ord would reject.
+    pub fn build(self) -> Result<Runestone, BuildRunestoneError> {
+        if let Some(etching) = &self.runestone.etching {
+            validate_etching(etching)?;
+        }
+        if let Some(id @ RuneId { block: 0, tx }) = self.runestone.mint {
+            if tx != 0 {
+                return Err(BuildRunestoneError::InvalidMintId(id));
+            }
+        }
+        Ok(self.runestone)
+    }
+
+    pub fn script(self) -> Result<ScriptBuf, BuildRunestoneError> {
+        Ok(self.build()?.encipher())
+    }
+
+    /// The runestone as a zero value OP_RETURN output.
+    pub fn tx_out(self) -> Result<TxOut, BuildRunestoneError> {
+        Ok(TxOut {
+            value: Amount::ZERO,
+            script_pubkey: self.script()?,
+        })
+    }
+
+    fn etching(&mut self) -> &mut Etching {
+        self.runestone.etching.get_or_insert_with(Etching::default)
+    }
+}
+
+// the minimum name length depends on the network and etching height, ord checks that itself
+fn validate_etching(etching: &Etching) -> Result<(), BuildRunestoneError> {
+    let divisibility = etching.divisibility.unwrap_or_default();
+    if divisibility > Etching::MAX_DIVISIBILITY {
+        return Err(BuildRunestoneError::DivisibilityTooHigh(divisibility));
+    }
+    if let Some(rune) = etching.rune {
+        if rune.is_reserved() {
+            return Err(BuildRunestoneError::ReservedName(rune));
+        }
+        // a spacer can only sit between two letters
+        let spacers = etching.spacers.unwrap_or_default();
+        if spacers >> (rune.to_string().len() - 1) != 0 {
+            return Err(BuildRunestoneError::SpacersOutOfRange { rune, spacers });
+        }
+    } else if etching.spacers.is_some() {
+        return Err(BuildRunestoneError::SpacersWithoutName);
+    }
+    if etching.supply().is_none() {
+        return Err(BuildRunestoneError::SupplyOverflow);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ordinals::Rune;
+
+    use crate::data::transaction::tx_with_outputs;
+    use crate::decoder::rune_decode::RuneTransactionDecoder;
+    use crate::models::runes::DecodedRunestone;
+
+    use super::*;
+
+    const RUNE: RuneId = RuneId { block: 840000, tx: 3 };
+
+    fn decode(builder: RunestoneBuilder, outputs: usize) -> DecodedRunestone {
+        let mut tx = tx_with_outputs(vec![ScriptBuf::new(); outputs + 1]);
+        tx.output[0] = builder.tx_out().unwrap();
+        RuneTransactionDecoder::new()
+            .decode_tx_at(&tx, 840100, 2)
+            .unwrap()
+            .into_runestone()
+            .expect("Expected runestone")
+    }
+
+    #[test]
+    fn etching_round_trip() {
+        let terms = Terms {
+            amount: Some(1000),
+            cap: Some(21_000),
+            height: (Some(840100), Some(850000)),
+            offset: (None, Some(10_000)),
+        };
+        let decoded = decode(
+            RunestoneBuilder::new()
+                .etch(SpacedRune::from_str("UNCOMMON•GOODS").unwrap())
+                .divisibility(2)
+                .symbol('⧉')
+                .premine(5000)
+                .terms(terms)
+                .turbo(true)
+                .edict(RuneId::default(), 5000, 1),
+            1,
+        );
+        let etching = decoded.etching.unwrap();
+        assert_eq!(etching.rune_name.unwrap().to_string(), "UNCOMMON•GOODS");
+        assert!(!etching.reserved);
+        assert_eq!(etching.divisibility, 2);
+        assert_eq!(etching.symbol, Some('⧉'));
+        assert_eq!(etching.premine, 5000);
+        assert_eq!(etching.terms, Some(terms));
+        assert!(etching.turbo);
+        assert_eq!(etching.supply, Some(5000 + 1000 * 21_000));
+        assert_eq!(decoded.edicts, vec![Edict { id: RuneId::default(), amount: 5000, output: 1 }]);
+    }
+
+    #[test]
+    fn reserved_etching_round_trip() {
+        let decoded = decode(RunestoneBuilder::new().etch_reserved().premine(1), 1);
+        let etching = decoded.etching.unwrap();
+        assert!(etching.reserved);
+        assert_eq!(etching.rune_name.unwrap().rune, Rune::reserved(840100, 2));
+        assert_eq!(etching.premine, 1);
+    }
+
+    #[test]
+    fn mint_and_transfer_round_trip() {
+        let other = RuneId { block: 840010, tx: 4 };
+        let decoded = decode(
+            RunestoneBuilder::new()
+                .mint(RUNE)
+                .edict(other, 7, 2)
+                .edict(RUNE, 100, 1)
+                .edict(RUNE, 0, 3)
+                .pointer(2),
+            3,
+        );
+        assert!(!decoded.is_etching());
+        assert_eq!(decoded.mint, Some(RUNE));
+        assert_eq!(decoded.pointer, Some(2));
+        assert_eq!(
+            decoded.edicts,
+            vec![
+                Edict { id: RUNE, amount: 100, output: 1 },
+                Edict { id: RUNE, amount: 0, output: 3 },
+                Edict { id: other, amount: 7, output: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        let error = |builder: RunestoneBuilder| builder.build().unwrap_err();
+        assert_eq!(
+            error(RunestoneBuilder::new().etch_reserved().divisibility(39)),
+            BuildRunestoneError::DivisibilityTooHigh(39)
+        );
+        let reserved = Rune::reserved(840000, 1);
+        assert_eq!(
+            error(RunestoneBuilder::new().etch(SpacedRune::new(reserved, 0))),
+            BuildRunestoneError::ReservedName(reserved)
+        );
+        let ab = Rune::from_str("AB").unwrap();
+        assert_eq!(
+            error(RunestoneBuilder::new().etch(SpacedRune::new(ab, 0b10))),
+            BuildRunestoneError::SpacersOutOfRange { rune: ab, spacers: 0b10 }
+        );
+        assert_eq!(
+            error(RunestoneBuilder::new().etch_reserved().premine(u128::MAX).terms(Terms {
+                amount: Some(1),
+                cap: Some(1),
+                ..Default::default()
+            })),
+            BuildRunestoneError::SupplyOverflow
+        );
+        let mint = RuneId { block: 0, tx: 1 };
+        assert_eq!(error(RunestoneBuilder::new().mint(mint)), BuildRunestoneError::InvalidMintId(mint));
+        assert_eq!(error(RunestoneBuilder::new().mint(mint)).to_string(), "invalid mint rune id 0:1");
+        assert!(RunestoneBuilder::new().etch(SpacedRune::new(Rune::from_str("AB").unwrap(), 0b1)).build().is_ok());
+    }
+}
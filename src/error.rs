@@ -0,0 +1,114 @@
+use std::fmt;
+
+use bitcoin::Network;
+use reqwest::StatusCode;
+
+#[derive(Debug)]
+pub enum OrdClientError {
+    /// The request never produced a response (connection refused, timeout, TLS, ...).
+    Transport(reqwest::Error),
+    /// The server answered with a non-success status other than 404.
+    Status {
+        url: String,
+        status: StatusCode,
+        body: String,
+    },
+    /// The server answered 404 for the requested resource.
+    NotFound { url: String },
+    /// The response body could not be decoded into the expected type.
+    Decode {
+        url: String,
+        source: serde_json::Error,
+        payload: String,
+    },
+    /// The response body is larger than the limit the caller asked for.
+    ContentTooLarge { url: String, limit: u64 },
+    /// The arguments passed to the client were rejected before any request was made.
+    InvalidInput(String),
+    /// An address, passed in or returned by the server, belongs to another network than the client's.
+    WrongNetwork { address: String, network: Network },
+}
+
+impl OrdClientError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, OrdClientError::NotFound { .. })
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            OrdClientError::Status { status, .. } => Some(*status),
+            OrdClientError::NotFound { .. } => Some(StatusCode::NOT_FOUND),
+            OrdClientError::Transport(err) => err.status(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for OrdClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrdClientError::Transport(err) => write!(f, "request failed: {}", err),
+            OrdClientError::Status { url, status, body } => {
+                write!(f, "{} returned {}: {}", url, status, body)
+            }
+            OrdClientError::NotFound { url } => write!(f, "{} not found", url),
+            OrdClientError::Decode { url, source, .. } => {
+                write!(f, "failed to decode response from {}: {}", url, source)
+            }
+            OrdClientError::ContentTooLarge { url, limit } => {
+                write!(f, "{} is larger than {} bytes", url, limit)
+            }
+            OrdClientError::InvalidInput(message) => write!(f, "invalid input: {}", message),
+            OrdClientError::WrongNetwork { address, network } => {
+                write!(f, "address {} is not valid on {}", address, network)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrdClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OrdClientError::Transport(err) => Some(err),
+            OrdClientError::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for OrdClientError {
+    fn from(err: reqwest::Error) -> Self {
+        OrdClientError::Transport(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn not_found_reports_status() {
+        let err = OrdClientError::NotFound {
+            url: "http://localhost/rune/1:0".to_string(),
+        };
+        assert!(err.is_not_found());
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(err.to_string(), "http://localhost/rune/1:0 not found");
+    }
+
+    #[test]
+    fn decode_keeps_payload() {
+        let source = serde_json::from_str::<u64>("oops").unwrap_err();
+        let err = OrdClientError::Decode {
+            url: "http://localhost/blockheight".to_string(),
+            source,
+            payload: "oops".to_string(),
+        };
+        assert!(!err.is_not_found());
+        assert_eq!(err.status(), None);
+        match err {
+            OrdClientError::Decode { payload, .. } => assert_eq!(payload, "oops"),
+            _ => panic!("Expected decode error"),
+        }
+    }
+}
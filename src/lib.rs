@@ -1,4 +1,10 @@
 pub mod ord_client;
 pub mod decoder;
+pub mod encoder;
 pub mod models;
 pub mod data;
+pub mod error;
+pub mod auth;
+pub mod retry;
+#[cfg(test)]
+mod test_server;
@@ -1,43 +1,25 @@
-use std::fmt;
-use serde::{de, Deserialize, Deserializer, Serialize};
-use serde::de::Visitor;
+use bitcoin::address::NetworkUnchecked;
+use bitcoin::{Address, Network};
+use serde::{Deserialize, Serialize};
 
-pub fn string_or_number<'de, D>(deserializer: D) -> Result<f64, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct StringOrNumberVisitor;
+use crate::error::OrdClientError;
+use crate::models::amount::RuneAmount;
 
-    impl<'de> Visitor<'de> for StringOrNumberVisitor {
-        type Value = f64;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a string or a number")
-        }
-
-        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            Ok(value)
-        }
-
-        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
-        where
-            E: de::Error,
-        {
-            value.parse::<f64>().map_err(E::custom)
-        }
-    }
-
-    deserializer.deserialize_any(StringOrNumberVisitor)
+/// Check an address belongs to `network`. Testnet, testnet4 and signet share their
+/// address formats so they accept each other's addresses.
+pub(crate) fn require_network(address: Address<NetworkUnchecked>, network: Network) -> Result<Address, OrdClientError> {
+    let formatted = address.assume_checked_ref().to_string();
+    address
+        .require_network(network)
+        .map_err(|_| OrdClientError::WrongNetwork { address: formatted, network })
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RuneBalance {
     pub rune_name: String,
-    #[serde(deserialize_with = "string_or_number")]
-    pub balance: f64,
+    /// Decimal amount as ord formats it, with as many decimals as the string has rather than
+    /// the rune's divisibility. `rescale` it to the divisibility to get base units.
+    pub balance: RuneAmount,
     pub rune_symbol: Option<String>,
 }
 #[derive(Serialize, Deserialize, Debug)]
@@ -73,7 +55,7 @@ mod tests {
         assert_eq!(address_response.outputs, vec!["ab"]);
         assert_eq!(address_response.inscriptions, vec!["jkjlk"]);
         assert_eq!(address_response.runes_balances[0].rune_name, "SAIKO•HAMSTER");
-        assert_eq!(address_response.runes_balances[0].balance, 10150.0);
+        assert_eq!(address_response.runes_balances[0].balance, RuneAmount::new(10150, 0));
         assert_eq!(address_response.runes_balances[0].rune_symbol, Some("🐹".to_string()));
     }
     #[test]
@@ -97,7 +79,29 @@ mod tests {
         assert_eq!(address_response.outputs, vec!["ab"]);
         assert_eq!(address_response.inscriptions, vec!["jkjlk"]);
         assert_eq!(address_response.runes_balances[0].rune_name, "SAIKO•HAMSTER");
-        assert_eq!(address_response.runes_balances[0].balance, 10150.0);
+        assert_eq!(address_response.runes_balances[0].balance, RuneAmount::new(10150, 0));
         assert_eq!(address_response.runes_balances[0].rune_symbol, None);
     }
+
+    #[test]
+    fn test_exact_decimal_balance() {
+        let json_data = r#"
+                {
+                  "outputs": [],
+                  "inscriptions": [],
+                  "sat_balance": 546,
+                  "runes_balances": [
+                    [
+                      "DOG•GO•TO•THE•MOON",
+                      "90071992547.40993",
+                      "🐕"
+                    ]
+                  ]
+                }
+                "#;
+        let address_response: AddressResponse = serde_json::from_str(json_data).unwrap();
+        let balance = address_response.runes_balances[0].balance;
+        assert_eq!(balance, RuneAmount::new(9007199254740993, 5));
+        assert_eq!(balance.to_string(), "90071992547.40993");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,319 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use ordinals::Pile;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Maximum divisibility a rune can be etched with.
+pub const MAX_DIVISIBILITY: u8 = 38;
+
+/// Exact rune amount: `amount` units of `10^-divisibility`.
+///
+/// `RuneAmount { amount: 150, divisibility: 2 }` is `1.5`. Formatting follows ord's `Pile`,
+/// and parsing accepts the decimal strings ord serves, e.g. `"10150"` or `"0.00000001"`.
+///
+/// A parsed amount keeps the decimals of its string, not the rune's divisibility: `"1.5"`
+/// is `{ amount: 15, divisibility: 1 }` for any rune, so `amount` is only in base units of
+/// the rune once [`rescale`](Self::rescale)d to its divisibility, as [`parse`](Self::parse)
+/// does. Equality and hashing compare values, `{ 15, 1 }` equals `{ 150, 2 }`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuneAmount {
+    pub amount: u128,
+    pub divisibility: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRuneAmountError {
+    Empty,
+    InvalidCharacter(char),
+    Overflow,
+    TooManyDecimals { divisibility: u8 },
+}
+
+impl fmt::Display for ParseRuneAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRuneAmountError::Empty => write!(f, "empty rune amount"),
+            ParseRuneAmountError::InvalidCharacter(c) => {
+                write!(f, "invalid character {:?} in rune amount", c)
+            }
+            ParseRuneAmountError::Overflow => write!(f, "rune amount out of range"),
+            ParseRuneAmountError::TooManyDecimals { divisibility } => {
+                write!(f, "rune amount has more than {} decimals", divisibility)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseRuneAmountError {}
+
+impl RuneAmount {
+    pub fn new(amount: u128, divisibility: u8) -> Self {
+        RuneAmount { amount, divisibility }
+    }
+
+    /// Parse a decimal string into base units of a rune with the given divisibility,
+    /// e.g. `"1.5"` with divisibility 8 is `150000000`.
+    pub fn parse(s: &str, divisibility: u8) -> Result<Self, ParseRuneAmountError> {
+        let parsed = RuneAmount::from_str(s)?;
+        parsed
+            .rescale(divisibility)
+            .ok_or(if divisibility < parsed.divisibility {
+                ParseRuneAmountError::TooManyDecimals { divisibility }
+            } else {
+                ParseRuneAmountError::Overflow
+            })
+    }
+
+    /// Same value expressed with another divisibility, `None` if precision would be lost
+    /// or the amount does not fit in a `u128`.
+    pub fn rescale(&self, divisibility: u8) -> Option<Self> {
+        if divisibility > MAX_DIVISIBILITY {
+            return None;
+        }
+        let amount = if divisibility >= self.divisibility {
+            let factor = 10u128.checked_pow((divisibility - self.divisibility).into())?;
+            self.amount.checked_mul(factor)?
+        } else {
+            let factor = 10u128.checked_pow((self.divisibility - divisibility).into())?;
+            if !self.amount.is_multiple_of(factor) {
+                return None;
+            }
+            self.amount / factor
+        };
+        Some(RuneAmount { amount, divisibility })
+    }
+
+    /// Same value with the fewest decimals, e.g. `{ 150, 2 }` is `{ 15, 1 }`.
+    pub fn normalized(&self) -> Self {
+        let mut normalized = *self;
+        while normalized.divisibility > 0 && normalized.amount.is_multiple_of(10) {
+            normalized.amount /= 10;
+            normalized.divisibility -= 1;
+        }
+        normalized
+    }
+
+    /// Whole units, truncating the fractional part.
+    pub fn whole(&self) -> u128 {
+        match 10u128.checked_pow(self.divisibility.into()) {
+            Some(factor) => self.amount / factor,
+            None => 0,
+        }
+    }
+
+    pub fn to_pile(&self, symbol: Option<char>) -> Pile {
+        Pile {
+            amount: self.amount,
+            divisibility: self.divisibility,
+            symbol,
+        }
+    }
+}
+
+impl PartialEq for RuneAmount {
+    fn eq(&self, other: &Self) -> bool {
+        let (this, other) = (self.normalized(), other.normalized());
+        this.amount == other.amount && this.divisibility == other.divisibility
+    }
+}
+
+impl Eq for RuneAmount {}
+
+impl Hash for RuneAmount {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let normalized = self.normalized();
+        normalized.amount.hash(state);
+        normalized.divisibility.hash(state);
+    }
+}
+
+impl fmt::Display for RuneAmount {
+    // same as `Pile`'s formatting, without the trailing symbol
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(cutoff) = 10u128.checked_pow(self.divisibility.into()) else {
+            return write!(f, "{}e-{}", self.amount, self.divisibility);
+        };
+        let whole = self.amount / cutoff;
+        let mut fractional = self.amount % cutoff;
+        if fractional == 0 {
+            return write!(f, "{}", whole);
+        }
+        let mut width = usize::from(self.divisibility);
+        while fractional.is_multiple_of(10) {
+            fractional /= 10;
+            width -= 1;
+        }
+        write!(f, "{}.{:0>width$}", whole, fractional, width = width)
+    }
+}
+
+impl FromStr for RuneAmount {
+    type Err = ParseRuneAmountError;
+
+    /// Parses a plain decimal, keeping as many decimals as the string has.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(ParseRuneAmountError::Empty);
+        }
+        if fraction.len() > MAX_DIVISIBILITY as usize {
+            return Err(ParseRuneAmountError::TooManyDecimals {
+                divisibility: MAX_DIVISIBILITY,
+            });
+        }
+        let mut amount: u128 = 0;
+        for c in integer.chars().chain(fraction.chars()) {
+            let digit = c.to_digit(10).ok_or(ParseRuneAmountError::InvalidCharacter(c))?;
+            amount = amount
+                .checked_mul(10)
+                .and_then(|amount| amount.checked_add(digit.into()))
+                .ok_or(ParseRuneAmountError::Overflow)?;
+        }
+        Ok(RuneAmount {
+            amount,
+            divisibility: fraction.len() as u8,
+        })
+    }
+}
+
+impl Serialize for RuneAmount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RuneAmount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RuneAmountVisitor)
+    }
+}
+
+struct RuneAmountVisitor;
+
+impl<'de> Visitor<'de> for RuneAmountVisitor {
+    type Value = RuneAmount;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal string or an integer")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(RuneAmount::new(value.into(), 0))
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Self::Value, E> {
+        Ok(RuneAmount::new(value, 0))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        u64::try_from(value)
+            .map(|value| RuneAmount::new(value.into(), 0))
+            .map_err(|_| E::custom("negative rune amount"))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        // ord serves strings or integers, only accept floats that are exact integers
+        const MAX_EXACT: f64 = 9_007_199_254_740_992.0; // 2^53
+        if value.fract() != 0.0 || !(0.0..MAX_EXACT).contains(&value) {
+            return Err(E::custom(format!("rune amount {} is not an exact integer", value)));
+        }
+        Ok(RuneAmount::new(value as u128, 0))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keeps_scale() {
+        assert_eq!(RuneAmount::from_str("10150").unwrap(), RuneAmount::new(10150, 0));
+        assert_eq!(RuneAmount::from_str("1.50").unwrap().divisibility, 2);
+        assert_eq!(RuneAmount::from_str("1.50").unwrap().amount, 150);
+        assert_eq!(RuneAmount::from_str(".5").unwrap(), RuneAmount::new(5, 1));
+        assert_eq!(RuneAmount::from_str("").unwrap_err(), ParseRuneAmountError::Empty);
+        assert_eq!(
+            RuneAmount::from_str("1,5").unwrap_err(),
+            ParseRuneAmountError::InvalidCharacter(',')
+        );
+        assert_eq!(RuneAmount::from_str("-1").unwrap_err(), ParseRuneAmountError::InvalidCharacter('-'));
+    }
+
+    #[test]
+    fn parse_with_divisibility() {
+        assert_eq!(RuneAmount::parse("1.5", 8).unwrap(), RuneAmount::new(150_000_000, 8));
+        assert_eq!(RuneAmount::parse("71510.41666667", 8).unwrap().amount, 7151041666667);
+        assert_eq!(
+            RuneAmount::parse("0.001", 2).unwrap_err(),
+            ParseRuneAmountError::TooManyDecimals { divisibility: 2 }
+        );
+    }
+
+    #[test]
+    fn exact_above_f64_precision() {
+        // 2^53 + 1 is not representable as an f64
+        let amount = RuneAmount::from_str("9007199254740993").unwrap();
+        assert_eq!(amount.amount, 9_007_199_254_740_993);
+        let max = RuneAmount::from_str(&u128::MAX.to_string()).unwrap();
+        assert_eq!(max.amount, u128::MAX);
+        assert_eq!(
+            RuneAmount::from_str("340282366920938463463374607431768211456").unwrap_err(),
+            ParseRuneAmountError::Overflow
+        );
+    }
+
+    #[test]
+    fn display_matches_pile() {
+        assert_eq!(RuneAmount::new(7151041666667, 8).to_string(), "71510.41666667");
+        assert_eq!(RuneAmount::new(150, 2).to_string(), "1.5");
+        assert_eq!(RuneAmount::new(100, 2).to_string(), "1");
+        assert_eq!(RuneAmount::new(1, 38).to_string(), "0.00000000000000000000000000000000000001");
+        assert_eq!(RuneAmount::new(5, 0).to_pile(Some('🐾')).to_string(), "5\u{A0}🐾");
+    }
+
+    #[test]
+    fn rescale() {
+        let amount = RuneAmount::new(150, 2);
+        assert_eq!(amount.rescale(8), Some(RuneAmount::new(150_000_000, 8)));
+        assert_eq!(amount.rescale(1), Some(RuneAmount::new(15, 1)));
+        assert_eq!(amount.rescale(0), None);
+        assert_eq!(RuneAmount::new(u128::MAX, 0).rescale(1), None);
+        assert_eq!(RuneAmount::new(12345, 2).whole(), 123);
+    }
+
+    #[test]
+    fn serde_string_or_number() {
+        assert_eq!(serde_json::from_str::<RuneAmount>("\"1.5\"").unwrap(), RuneAmount::new(15, 1));
+        assert_eq!(serde_json::from_str::<RuneAmount>("10150").unwrap(), RuneAmount::new(10150, 0));
+        assert_eq!(
+            serde_json::from_str::<RuneAmount>("\"340282366920938463463374607431768211455\"").unwrap(),
+            RuneAmount::new(u128::MAX, 0)
+        );
+        assert_eq!(serde_json::to_string(&RuneAmount::new(15, 1)).unwrap(), "\"1.5\"");
+        assert_eq!(serde_json::from_str::<RuneAmount>("10150.0").unwrap(), RuneAmount::new(10150, 0));
+        assert!(serde_json::from_str::<RuneAmount>("1.5").is_err());
+        assert!(serde_json::from_str::<RuneAmount>("-1.0").is_err());
+        assert!(serde_json::from_str::<RuneAmount>("9007199254740992.0").is_err());
+        assert!(serde_json::from_str::<RuneAmount>("1e300").is_err());
+    }
+
+    #[test]
+    fn equal_values_compare_equal() {
+        use std::collections::HashSet;
+
+        assert_eq!(RuneAmount::new(15, 1), RuneAmount::new(150, 2));
+        assert_eq!(RuneAmount::new(0, 0), RuneAmount::new(0, 8));
+        assert_ne!(RuneAmount::new(15, 1), RuneAmount::new(15, 2));
+        assert_eq!(RuneAmount::new(1500, 3).normalized(), RuneAmount::new(15, 1));
+        assert_eq!(RuneAmount::new(1500, 3).normalized().divisibility, 1);
+        let set: HashSet<RuneAmount> = [RuneAmount::new(15, 1), RuneAmount::new(150, 2)].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bitcoin::{BlockHash, Transaction, Txid};
+use ordinals::SpacedRune;
+use serde::{Deserialize, Serialize};
+
+use crate::models::inscription::InscriptionId;
+
+/// A block addressed either by height or by hash, as accepted by `/block/{query}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockQuery {
+    Height(u32),
+    Hash(BlockHash),
+}
+
+impl fmt::Display for BlockQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockQuery::Height(height) => write!(f, "{}", height),
+            BlockQuery::Hash(hash) => write!(f, "{}", hash),
+        }
+    }
+}
+
+impl From<u32> for BlockQuery {
+    fn from(height: u32) -> Self {
+        BlockQuery::Height(height)
+    }
+}
+
+impl From<BlockHash> for BlockQuery {
+    fn from(hash: BlockHash) -> Self {
+        BlockQuery::Hash(hash)
+    }
+}
+
+/// Response of ord's `/block/{height|hash}` endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockResponse {
+    pub best_height: u32,
+    pub hash: BlockHash,
+    pub height: u32,
+    #[serde(default)]
+    pub inscriptions: Vec<InscriptionId>,
+    #[serde(default)]
+    pub runes: Vec<SpacedRune>,
+    pub target: BlockHash,
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+}
+
+impl BlockResponse {
+    pub fn txids(&self) -> Vec<Txid> {
+        self.transactions.iter().map(Transaction::compute_txid).collect()
+    }
+}
+
+/// Response of ord's `/blocks` endpoint: the most recent blocks and their featured inscriptions.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlocksResponse {
+    pub last: u32,
+    pub blocks: Vec<BlockHash>,
+    #[serde(default)]
+    pub featured_blocks: BTreeMap<BlockHash, Vec<InscriptionId>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use bitcoin::consensus::deserialize;
+    use hex::decode as hex_decode;
+
+    use crate::data::transaction::RUNE_BUY_TX;
+
+    use super::*;
+
+    const BLOCK_HASH: &str = "0000000000000000000320283a032748cef8227873ff4872689bf23f1cda83a5";
+
+    #[test]
+    fn block_query_display() {
+        assert_eq!(BlockQuery::from(840000).to_string(), "840000");
+        let hash = BlockHash::from_str(BLOCK_HASH).unwrap();
+        assert_eq!(BlockQuery::from(hash).to_string(), BLOCK_HASH);
+    }
+
+    #[test]
+    fn deserialize_block() {
+        let tx: Transaction = deserialize(&hex_decode(RUNE_BUY_TX).unwrap()).unwrap();
+        let json = serde_json::json!({
+            "best_height": 864000,
+            "hash": BLOCK_HASH,
+            "height": 840000,
+            "inscriptions": ["6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0"],
+            "runes": ["DOG•GO•TO•THE•MOON"],
+            "target": "0000000000000000000342190000000000000000000000000000000000000000",
+            "transactions": [tx],
+        });
+        let block: BlockResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(block.height, 840000);
+        assert_eq!(block.best_height, 864000);
+        assert_eq!(block.hash.to_string(), BLOCK_HASH);
+        assert_eq!(block.inscriptions.len(), 1);
+        assert_eq!(block.runes[0].to_string(), "DOG•GO•TO•THE•MOON");
+        assert_eq!(block.txids(), vec![tx.compute_txid()]);
+    }
+
+    #[test]
+    fn deserialize_blocks() {
+        let json = format!(r#"{{
+            "last": 840000,
+            "blocks": ["{hash}"],
+            "featured_blocks": {{
+                "{hash}": ["6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0"]
+            }}
+        }}"#, hash = BLOCK_HASH);
+        let blocks: BlocksResponse = serde_json::from_str(&json).unwrap();
+        assert_eq!(blocks.last, 840000);
+        assert_eq!(blocks.blocks.len(), 1);
+        assert_eq!(blocks.featured_blocks[&blocks.blocks[0]].len(), 1);
+    }
+}
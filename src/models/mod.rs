@@ -1,3 +1,7 @@
 pub mod runes;
 pub mod ordinals;
-pub mod address;
\ No newline at end of file
+pub mod address;
+pub mod amount;
+pub mod inscription;
+pub mod block;
+pub mod transaction;
\ No newline at end of file
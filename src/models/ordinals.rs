@@ -1,21 +1,78 @@
 use std::collections::HashMap;
+use bitcoin::address::{NetworkChecked, NetworkUnchecked, NetworkValidation};
+use bitcoin::{Address, Network};
 use serde::{Deserialize, Serialize};
 
+use crate::error::OrdClientError;
+use crate::models::address::require_network;
+use crate::models::amount::RuneAmount;
+
+/// Response of ord's `/output/{outpoint}` endpoint. It deserializes as
+/// `OutputResponse<NetworkUnchecked>`; `OrdClient::fetch_output` returns it with the
+/// address checked against the client's network.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct OutputResponse {
-    pub address: String,
+#[serde(bound(serialize = "Address<V>: Serialize", deserialize = "Address<V>: Deserialize<'de>"))]
+pub struct OutputResponse<V: NetworkValidation = NetworkChecked> {
+    /// `None` for scripts without an address, e.g. OP_RETURN.
+    #[serde(default)]
+    pub address: Option<Address<V>>,
     pub inscriptions: Vec<String>,
     pub runes: HashMap<String, Rune>,
     pub transaction: String,
     pub value: u64,
 }
 
+impl OutputResponse<NetworkUnchecked> {
+    /// Check the address belongs to `network`.
+    pub fn require_network(self, network: Network) -> Result<OutputResponse, OrdClientError> {
+        Ok(OutputResponse {
+            address: self.address.map(|address| require_network(address, network)).transpose()?,
+            inscriptions: self.inscriptions,
+            runes: self.runes,
+            transaction: self.transaction,
+            value: self.value,
+        })
+    }
+}
+
+/// Rune balance of an output. ord serves the amount in base units next to the rune's
+/// divisibility, both end up in `amount`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "RawRune", into = "RawRune")]
 pub struct Rune {
-    pub amount: f64,
-    pub divisibility: Option<u8>,
+    /// Balance with the rune's divisibility, so `amount.amount` is in base units. The
+    /// divisibility is 0 when ord leaves it out.
+    pub amount: RuneAmount,
     pub symbol: Option<String>,
 }
+
+// the shape ord serves
+#[derive(Serialize, Deserialize)]
+struct RawRune {
+    amount: u128,
+    divisibility: Option<u8>,
+    symbol: Option<String>,
+}
+
+impl From<RawRune> for Rune {
+    fn from(raw: RawRune) -> Self {
+        Rune {
+            amount: RuneAmount::new(raw.amount, raw.divisibility.unwrap_or(0)),
+            symbol: raw.symbol,
+        }
+    }
+}
+
+impl From<Rune> for RawRune {
+    fn from(rune: Rune) -> Self {
+        RawRune {
+            amount: rune.amount.amount,
+            divisibility: Some(rune.amount.divisibility),
+            symbol: rune.symbol,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,9 +88,9 @@ mod tests {
             "transaction": "3de0c436d136abfb5f1ec1996d755331f25bf8e424743b1c21e2952fea8ef002",
             "value": 546
         }"#;
-        let output_response: OutputResponse = serde_json::from_str(output_response).unwrap();
+        let output_response: OutputResponse<NetworkUnchecked> = serde_json::from_str(output_response).unwrap();
         assert_eq!(output_response.value, 546);
-        assert_eq!(output_response.address, "bc1p90zah9c3hyywydpgnw0gcuk2pwwywj8u7hd0rhhr8kg0x3wl778s4d8h9t");
+        assert_eq!(output_response.address.unwrap().assume_checked().to_string(), "bc1p90zah9c3hyywydpgnw0gcuk2pwwywj8u7hd0rhhr8kg0x3wl778s4d8h9t");
     }
 
     #[tokio::test]
@@ -49,9 +106,9 @@ mod tests {
             "transaction": "9967981989ae3c945cc2174d5ff7560af9d6d76a08ecc1eff2d854add40679ec",
             "value": 286588
         }"#;
-        let output_response: OutputResponse = serde_json::from_str(output_response).unwrap();
+        let output_response: OutputResponse<NetworkUnchecked> = serde_json::from_str(output_response).unwrap();
         assert_eq!(output_response.value, 286588);
-        assert_eq!(output_response.address, "bc1q80c2nv7ryjcw2a6uj2p6avd26rkcw4dc90a6mr");
+        assert_eq!(output_response.address.unwrap().assume_checked().to_string(), "bc1q80c2nv7ryjcw2a6uj2p6avd26rkcw4dc90a6mr");
     }
 
     #[tokio::test]
@@ -74,9 +131,38 @@ mod tests {
             "value": 546
         }"#;
 
-        let output_response: OutputResponse = serde_json::from_str(output_response).unwrap();
+        let output_response: OutputResponse<NetworkUnchecked> = serde_json::from_str(output_response).unwrap();
         assert_eq!(output_response.value, 546);
-        assert_eq!(output_response.address, "bc1ppq9v5r7cu7w9nc408jyucvtpl2wnnw7kcdfu425z0f0e35f4h5yswtykl3");
-        assert_eq!(output_response.runes["KODA•FLUFFINGTON"].amount, 7151041666667.0);
+        assert_eq!(output_response.address.unwrap().assume_checked().to_string(), "bc1ppq9v5r7cu7w9nc408jyucvtpl2wnnw7kcdfu425z0f0e35f4h5yswtykl3");
+        let rune = &output_response.runes["KODA•FLUFFINGTON"];
+        assert_eq!(rune.amount.amount, 7151041666667);
+        assert_eq!(rune.amount.divisibility, 8);
+        assert_eq!(rune.amount.to_string(), "71510.41666667");
+    }
+
+    #[tokio::test]
+    async fn large_rune_amount_test() {
+        let output_response = r#"{
+            "address": "bc1ppq9v5r7cu7w9nc408jyucvtpl2wnnw7kcdfu425z0f0e35f4h5yswtykl3",
+            "inscriptions": [],
+            "runes": {
+                "DOG•GO•TO•THE•MOON": {
+                    "amount": 100000000000000000000001,
+                    "divisibility": 5,
+                    "symbol": "🐕"
+                }
+            },
+            "transaction": "9967981989ae3c945cc2174d5ff7560af9d6d76a08ecc1eff2d854add40679ec",
+            "value": 546
+        }"#;
+
+        let output_response: OutputResponse<NetworkUnchecked> = serde_json::from_str(output_response).unwrap();
+        let rune = &output_response.runes["DOG•GO•TO•THE•MOON"];
+        assert_eq!(rune.amount.amount, 100000000000000000000001);
+        assert_eq!(rune.amount.divisibility, 5);
+        assert_eq!(
+            serde_json::to_string(rune).unwrap(),
+            r#"{"amount":100000000000000000000001,"divisibility":5,"symbol":"🐕"}"#
+        );
     }
 }
\ No newline at end of file
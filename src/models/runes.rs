@@ -1,17 +1,251 @@
-use ordinals::{Edict, RuneId};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
+use ordinals::{Edict, Flaw, Rune, RuneId, SpacedRune, Terms};
+
+use crate::error::OrdClientError;
+
+/// Parameters of a rune etched by a runestone.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EtchingDetails {
     pub tx_id: String,
-    pub rune_name: String,
+    /// Spaced rune name, `None` only for a reserved name decoded without the tx's block position.
+    pub rune_name: Option<SpacedRune>,
+    /// The etching gave no name, so the rune gets the reserved name of its block and tx index.
+    pub reserved: bool,
+    pub divisibility: u8,
+    pub premine: u128,
+    pub symbol: Option<char>,
+    pub spacers: u32,
+    pub terms: Option<Terms>,
+    pub turbo: bool,
+    /// Premine plus every mint the terms allow, `None` if it overflows.
     pub supply: Option<u128>,
     pub mintable: bool,
+    /// First input whose tapscript pushes the rune name's commitment. A named etching without
+    /// one is never indexed by ord, reserved names need no commitment.
+    pub commitment_input: Option<u32>,
+}
+
+/// Output spent by an etching's commitment input, as known to the caller's chain source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitOutput {
+    /// Height of the block that confirmed the output.
+    pub height: u64,
+    /// ord only accepts commitments spending a taproot output.
+    pub taproot: bool,
+}
+
+/// Whether ord would accept an etching: a named rune must not be reserved nor shorter than
+/// the minimum name at the etching height, and must be committed to by an input spending a
+/// taproot output with at least six confirmations at the etching height. Whether the name
+/// was already etched needs ord's index and is not checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EtchingValidity {
+    /// Etched name, `None` for a reserved name.
+    pub rune: Option<Rune>,
+    /// Input pushing the commitment, the one that satisfies the rules if any does.
+    pub commitment_input: Option<u32>,
+    /// Height the output spent by `commitment_input` was confirmed at, if the lookup knew it.
+    pub commit_height: Option<u64>,
+    pub valid: bool,
+}
+
+/// Everything a runestone does: it can etch, mint and transfer in the same transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecodedRunestone {
+    pub etching: Option<EtchingDetails>,
+    pub mint: Option<RuneId>,
+    pub edicts: Vec<Edict>,
+    /// Output receiving unallocated runes, the first non-OP_RETURN output when unset.
+    pub pointer: Option<u32>,
+}
+
+impl DecodedRunestone {
+    pub fn is_etching(&self) -> bool {
+        self.etching.is_some()
+    }
+
+    pub fn is_mint(&self) -> bool {
+        self.mint.is_some()
+    }
+
+    pub fn is_transfer(&self) -> bool {
+        !self.edicts.is_empty()
+    }
 }
-pub enum RuneTransaction {
-    ETCHING(EtchingDetails),
-    MINT(RuneId),
-    TRANSFER(Vec<Edict>),
+
+/// A malformed runestone. All runes input to the transaction are burned, an etched
+/// rune is created with no supply and a mint is counted but its amount is burned.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DecodedCenotaph {
+    pub flaw: Option<Flaw>,
+    pub etching: Option<Rune>,
+    pub mint: Option<RuneId>,
+}
+
+// unboxed like ordinals' own `Artifact`, runestones are by far the common case
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodedArtifact {
+    Runestone(DecodedRunestone),
+    Cenotaph(DecodedCenotaph),
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RuneTxDetails {
     pub tx_id: String,
-    pub rune_tx: RuneTransaction,
-}
\ No newline at end of file
+    pub artifact: DecodedArtifact,
+}
+
+impl RuneTxDetails {
+    pub fn runestone(&self) -> Option<&DecodedRunestone> {
+        match &self.artifact {
+            DecodedArtifact::Runestone(runestone) => Some(runestone),
+            DecodedArtifact::Cenotaph(_) => None,
+        }
+    }
+
+    pub fn cenotaph(&self) -> Option<&DecodedCenotaph> {
+        match &self.artifact {
+            DecodedArtifact::Cenotaph(cenotaph) => Some(cenotaph),
+            DecodedArtifact::Runestone(_) => None,
+        }
+    }
+
+    pub fn into_runestone(self) -> Option<DecodedRunestone> {
+        match self.artifact {
+            DecodedArtifact::Runestone(runestone) => Some(runestone),
+            DecodedArtifact::Cenotaph(_) => None,
+        }
+    }
+
+    pub fn is_cenotaph(&self) -> bool {
+        matches!(self.artifact, DecodedArtifact::Cenotaph(_))
+    }
+}
+
+/// Rune artifact of the `tx_index`-th tx of a block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockRuneArtifact {
+    pub tx_index: u32,
+    pub details: RuneTxDetails,
+    /// Id of the rune the tx etches, `{height}:{tx_index}`. Cenotaphs etch too, with no
+    /// supply. Whether ord accepts the etching also depends on its commitment.
+    pub etched: Option<RuneId>,
+}
+
+/// Rune activity of a block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockRuneCounts {
+    pub etchings: u64,
+    /// Runestones and cenotaphs naming a mint, per rune. These are attempts: whether ord
+    /// accepts a mint depends on the rune's terms and earlier mints, which a block alone
+    /// can't tell.
+    pub mint_attempts: BTreeMap<RuneId, u64>,
+    /// Runestones with at least one edict.
+    pub transfers: u64,
+    pub cenotaphs: u64,
+}
+
+/// Every rune artifact of a block, in tx order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockRunes {
+    pub height: u64,
+    pub artifacts: Vec<BlockRuneArtifact>,
+    pub counts: BlockRuneCounts,
+}
+
+/// A rune addressed by id, by name or by spaced name, as accepted by ord's `/rune/{query}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuneQuery {
+    Id(RuneId),
+    Name(Rune),
+    Spaced(SpacedRune),
+}
+
+impl RuneQuery {
+    // ord looks runes up by name, spacers are only cosmetic so they are left out of the path
+    pub(crate) fn path_segment(&self) -> String {
+        match self {
+            RuneQuery::Id(rune_id) => rune_id.to_string(),
+            RuneQuery::Name(rune) => rune.to_string(),
+            RuneQuery::Spaced(spaced_rune) => spaced_rune.rune.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for RuneQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuneQuery::Id(rune_id) => write!(f, "{}", rune_id),
+            RuneQuery::Name(rune) => write!(f, "{}", rune),
+            RuneQuery::Spaced(spaced_rune) => write!(f, "{}", spaced_rune),
+        }
+    }
+}
+
+impl FromStr for RuneQuery {
+    type Err = OrdClientError;
+
+    /// Parses `840000:3` as a rune id and anything else as a (possibly spaced) rune name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(rune_id) = RuneId::from_str(s) {
+            return Ok(RuneQuery::Id(rune_id));
+        }
+        let spaced_rune = SpacedRune::from_str(s)
+            .map_err(|err| OrdClientError::InvalidInput(format!("invalid rune {}: {}", s, err)))?;
+        if spaced_rune.spacers == 0 {
+            Ok(RuneQuery::Name(spaced_rune.rune))
+        } else {
+            Ok(RuneQuery::Spaced(spaced_rune))
+        }
+    }
+}
+
+impl From<RuneId> for RuneQuery {
+    fn from(rune_id: RuneId) -> Self {
+        RuneQuery::Id(rune_id)
+    }
+}
+
+impl From<Rune> for RuneQuery {
+    fn from(rune: Rune) -> Self {
+        RuneQuery::Name(rune)
+    }
+}
+
+impl From<SpacedRune> for RuneQuery {
+    fn from(spaced_rune: SpacedRune) -> Self {
+        RuneQuery::Spaced(spaced_rune)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rune_query() {
+        assert_eq!(
+            RuneQuery::from_str("840000:3").unwrap(),
+            RuneQuery::Id(RuneId { block: 840000, tx: 3 })
+        );
+        let spaced = RuneQuery::from_str("DOG•GO•TO•THE•MOON").unwrap();
+        assert!(matches!(spaced, RuneQuery::Spaced(_)));
+        assert_eq!(spaced.to_string(), "DOG•GO•TO•THE•MOON");
+        assert_eq!(spaced.path_segment(), "DOGGOTOTHEMOON");
+        assert_eq!(
+            RuneQuery::from_str("UNCOMMONGOODS").unwrap(),
+            RuneQuery::Name(Rune::from_str("UNCOMMONGOODS").unwrap())
+        );
+    }
+
+    #[test]
+    fn invalid_rune_query() {
+        assert!(matches!(RuneQuery::from_str("dog"), Err(OrdClientError::InvalidInput(_))));
+        assert!(RuneQuery::from_str("DOG••GO").is_err());
+        assert!(RuneQuery::from_str("").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use bitcoin::{Transaction, Txid};
+use ordinals::{Artifact, SpacedRune};
+use serde::{Deserialize, Serialize};
+
+use crate::models::inscription::InscriptionEnvelope;
+
+/// Response of ord's `/tx/{txid}` endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TransactionResponse {
+    pub chain: String,
+    pub etching: Option<SpacedRune>,
+    pub inscription_count: u32,
+    pub transaction: Transaction,
+    pub txid: Txid,
+}
+
+/// Response of ord's `/decode/{txid}` endpoint: the envelopes and runestone as ord sees them.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DecodeResponse {
+    #[serde(default)]
+    pub inscriptions: Vec<InscriptionEnvelope>,
+    pub runestone: Option<Artifact>,
+}
+
+#[cfg(test)]
+mod tests {
+    use bitcoin::consensus::deserialize;
+    use hex::decode as hex_decode;
+    use ordinals::Runestone;
+
+    use crate::data::transaction::RUNE_REVEAL_TX_HEX;
+
+    use super::*;
+
+    #[test]
+    fn deserialize_transaction() {
+        let tx: Transaction = deserialize(&hex_decode(RUNE_REVEAL_TX_HEX).unwrap()).unwrap();
+        let json = serde_json::json!({
+            "chain": "mainnet",
+            "etching": "HOOOOOOOOTERS",
+            "inscription_count": 1,
+            "transaction": tx,
+            "txid": tx.compute_txid(),
+        });
+        let response: TransactionResponse = serde_json::from_value(json).unwrap();
+        assert_eq!(response.chain, "mainnet");
+        assert_eq!(response.etching.unwrap().to_string(), "HOOOOOOOOTERS");
+        assert_eq!(response.inscription_count, 1);
+        assert_eq!(response.transaction, tx);
+        assert_eq!(response.txid, tx.compute_txid());
+    }
+
+    #[test]
+    fn deserialize_decode() {
+        let tx: Transaction = deserialize(&hex_decode(RUNE_REVEAL_TX_HEX).unwrap()).unwrap();
+        let json = serde_json::json!({
+            "inscriptions": [{
+                "input": 0,
+                "offset": 0,
+                "payload": {
+                    "body": [104, 105],
+                    "content_encoding": null,
+                    "content_type": b"text/plain;charset=utf-8".to_vec(),
+                    "delegate": null,
+                    "duplicate_field": false,
+                    "incomplete_field": false,
+                    "metadata": null,
+                    "metaprotocol": null,
+                    "parents": [],
+                    "pointer": null,
+                    "rune": [202, 198, 182, 118, 82, 172, 99, 11],
+                    "unrecognized_even_field": false
+                },
+                "pushnum": false,
+                "stutter": false
+            }],
+            "runestone": Runestone::decipher(&tx),
+        });
+        let response: DecodeResponse = serde_json::from_value(json).unwrap();
+        let envelope = &response.inscriptions[0];
+        assert_eq!(envelope.payload.body.as_deref(), Some(&b"hi"[..]));
+        assert_eq!(envelope.payload.content_type.as_deref(), Some(&b"text/plain;charset=utf-8"[..]));
+        assert_eq!(response.runestone, Runestone::decipher(&tx));
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// minimal HTTP/1.1 server used to exercise OrdClient against canned ord responses
+#[derive(Debug, Clone)]
+pub(crate) struct RecordedRequest {
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub(crate) struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    /// Send the body with chunked transfer encoding instead of a content-length.
+    pub chunked: bool,
+}
+
+impl MockResponse {
+    pub fn json(body: &str) -> Self {
+        MockResponse {
+            status: 200,
+            headers: vec![("content-type".to_string(), "application/json".to_string())],
+            body: body.as_bytes().to_vec(),
+            chunked: false,
+        }
+    }
+
+    pub fn status(status: u16, body: &str) -> Self {
+        MockResponse {
+            status,
+            headers: vec![],
+            body: body.as_bytes().to_vec(),
+            chunked: false,
+        }
+    }
+
+    pub fn bytes(body: &[u8]) -> Self {
+        MockResponse {
+            status: 200,
+            headers: vec![],
+            body: body.to_vec(),
+            chunked: false,
+        }
+    }
+
+    pub fn chunked(body: &[u8]) -> Self {
+        MockResponse {
+            chunked: true,
+            ..MockResponse::bytes(body)
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+pub(crate) struct TestServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl TestServer {
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler = Arc::new(handler);
+        let recorded = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    if reader.read_line(&mut request_line).is_err() {
+                        return;
+                    }
+                    let path = request_line
+                        .split_whitespace()
+                        .nth(1)
+                        .unwrap_or("/")
+                        .to_string();
+                    let mut headers = Vec::new();
+                    loop {
+                        let mut line = String::new();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            break;
+                        }
+                        let line = line.trim_end();
+                        if line.is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            headers.push((name.trim().to_string(), value.trim().to_string()));
+                        }
+                    }
+                    let request = RecordedRequest { path, headers };
+                    recorded.lock().unwrap().push(request.clone());
+                    let response = handler(&request);
+                    let length = if response.chunked {
+                        "transfer-encoding: chunked".to_string()
+                    } else {
+                        format!("content-length: {}", response.body.len())
+                    };
+                    let mut head = format!("HTTP/1.1 {} STATUS\r\n{}\r\nconnection: close\r\n", response.status, length);
+                    for (name, value) in &response.headers {
+                        head.push_str(&format!("{}: {}\r\n", name, value));
+                    }
+                    head.push_str("\r\n");
+                    let _ = stream.write_all(head.as_bytes());
+                    if response.chunked {
+                        let _ = stream.write_all(format!("{:x}\r\n", response.body.len()).as_bytes());
+                        let _ = stream.write_all(&response.body);
+                        let _ = stream.write_all(b"\r\n0\r\n\r\n");
+                    } else {
+                        let _ = stream.write_all(&response.body);
+                    }
+                });
+            }
+        });
+        TestServer { url, requests }
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}